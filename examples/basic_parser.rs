@@ -98,9 +98,7 @@ fn main() {
         println!("  Command: {}", cmd);
         match parser.execute(cmd) {
             Ok(response) => println!("  Response: {}", response),
-            Err(AtError::UnknownCommand) => println!("  Error: Unknown command"),
-            Err(AtError::NotSupported) => println!("  Error: Not supported"),
-            Err(AtError::InvalidArgs) => println!("  Error: Invalid arguments"),
+            Err(e) => println!("  Error: {}", e),
         }
         println!();
     }
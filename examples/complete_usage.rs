@@ -20,11 +20,16 @@
 //! Complete example demonstrating the AT command parser functionality
 
 use at_parser_rs::context::AtContext;
+use at_parser_rs::parser::dispatch;
+use at_parser_rs::response::BoolStyle;
 use at_parser_rs::{Args, AtError, AtResult};
 
 /// Echo command module - manages echo state
 pub struct EchoModule {
     pub echo: bool,
+    /// Boolean rendering, copied from the parser setting
+    /// (`AtParser::bool_style`) when the module is set up
+    pub style: BoolStyle,
 }
 
 impl AtContext for EchoModule {
@@ -39,11 +44,7 @@ impl AtContext for EchoModule {
 
     /// Query: return current echo value
     fn query(&mut self) -> AtResult<'static> {
-        if self.echo {
-            Ok("1")
-        } else {
-            Ok("0")
-        }
+        Ok(self.style.text(self.echo))
     }
 
     /// Test: show valid values
@@ -142,14 +143,8 @@ impl AtContext for LedModule {
         };
 
         // Optional brightness parameter
-        if let Some(brightness_str) = args.get(1) {
-            self.brightness = brightness_str
-                .parse::<u8>()
-                .map_err(|_| AtError::InvalidArgs)?;
-            
-            if self.brightness > 100 {
-                return Err(AtError::InvalidArgs);
-            }
+        if args.get(1).is_some() {
+            self.brightness = args.get_u32_in(1, 0, 100)? as u8;
         }
 
         if self.state {
@@ -163,32 +158,10 @@ impl AtContext for LedModule {
 /// Helper function to execute a command and print the result
 fn execute_command(cmd: &str, name: &str, module: &mut dyn AtContext) {
     println!("\n> {}", cmd);
-    
-    let result = if let Some(rest) = cmd.strip_prefix(name) {
-        if rest.is_empty() {
-            // Execute form: AT+CMD
-            module.exec()
-        } else if rest == "?" {
-            // Query form: AT+CMD?
-            module.query()
-        } else if rest == "=?" {
-            // Test form: AT+CMD=?
-            module.test()
-        } else if let Some(args_str) = rest.strip_prefix('=') {
-            // Set form: AT+CMD=args
-            module.set(Args { raw: args_str })
-        } else {
-            Err(AtError::InvalidArgs)
-        }
-    } else {
-        Err(AtError::UnknownCommand)
-    };
-    
-    match result {
+
+    match dispatch(module, name, cmd) {
         Ok(response) => println!("  Response: {}", response),
-        Err(AtError::UnknownCommand) => println!("  Error: Unknown command"),
-        Err(AtError::NotSupported) => println!("  Error: Operation not supported"),
-        Err(AtError::InvalidArgs) => println!("  Error: Invalid arguments"),
+        Err(e) => println!("  Error: {}", e),
     }
 }
 
@@ -197,7 +170,7 @@ fn main() {
     println!("Available commands: AT+ECHO, AT+RST, AT+INFO, AT+LED\n");
 
     // Create module instances
-    let mut echo = EchoModule { echo: false };
+    let mut echo = EchoModule { echo: false, style: BoolStyle::ZeroOne };
     let mut reset = ResetModule;
     let mut info = InfoModule { version: "v1.0.0" };
     let mut led = LedModule {
@@ -0,0 +1,107 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+//! Parser owning its response buffer
+//!
+//! `BufferedAtParser` wraps an `AtParser` together with a `[u8; N]` scratch
+//! buffer, so the framed response is rendered without the caller passing a
+//! buffer each time and the RAM used for responses is fixed at compile
+//! time by `N`.
+//!
+//! Borrowing: the returned `&str` borrows the wrapper mutably (it is tied
+//! to the `&mut self` of the call), so only one response is live at a time;
+//! it must be sent or copied before the next command is executed.
+//!
+//! Responses are text: binary payloads follow the `execute_framed` rules,
+//! use `AtParser::execute_into` for raw bytes.
+
+use crate::context::AtContext;
+use crate::parser::AtParser;
+use crate::response::ResponseWriter;
+use crate::AtError;
+
+/// `AtParser` wrapper rendering framed responses into an internal buffer
+/// of `N` bytes
+pub struct BufferedAtParser<'a, T, const N: usize> {
+    /// The wrapped parser
+    parser: AtParser<'a, T>,
+    /// Scratch buffer holding the last response
+    buf: [u8; N],
+}
+
+impl<'a, T, const N: usize> BufferedAtParser<'a, T, N> {
+
+    /// Wrap a parser
+    pub fn new(parser: AtParser<'a, T>) -> Self {
+        Self { parser, buf: [0; N] }
+    }
+
+    /// Get the wrapped parser
+    pub fn parser(&self) -> &AtParser<'a, T> {
+        &self.parser
+    }
+
+    /// Get the wrapped parser, e.g. to change its settings
+    pub fn parser_mut(&mut self) -> &mut AtParser<'a, T> {
+        &mut self.parser
+    }
+
+    /// Unwrap the parser, dropping the buffer
+    pub fn into_inner(self) -> AtParser<'a, T> {
+        self.parser
+    }
+}
+
+impl<'a, T, const N: usize> BufferedAtParser<'a, T, N>
+where
+    T: AtContext {
+
+    /// Parse and execute an AT command string, returning the framed
+    /// response (see `AtParser::execute_framed`)
+    ///
+    /// # Returns
+    /// * `Ok(&str)` - The framed response, valid until the next call
+    /// * `Err(AtError::BufferFull)` - The response does not fit in `N` bytes
+    pub fn execute(&mut self, input: &str) -> Result<&str, AtError> {
+        let mut out = ResponseWriter::new(&mut self.buf);
+        self.parser.execute_framed(input, &mut out).map_err(|_| AtError::BufferFull)?;
+        let len = out.len();
+        Ok(self.text(len))
+    }
+
+    /// Execute a batch of `;`-separated AT commands, returning the framed
+    /// response (see `AtParser::execute_line`)
+    ///
+    /// # Returns
+    /// * `Ok(&str)` - The framed response, valid until the next call
+    /// * `Err(AtError::BufferFull)` - The response does not fit in `N` bytes
+    pub fn execute_line(&mut self, line: &str) -> Result<&str, AtError> {
+        let mut out = ResponseWriter::new(&mut self.buf);
+        self.parser.execute_line(line, &mut out).map_err(|_| AtError::BufferFull)?;
+        let len = out.len();
+        Ok(self.text(len))
+    }
+
+    /// Get the first `len` bytes of the buffer as text
+    fn text(&self, len: usize) -> &str {
+        // Only `&str` values are written through `fmt::Write`, so this
+        // cannot fail
+        core::str::from_utf8(&self.buf[..len]).unwrap_or_default()
+    }
+}
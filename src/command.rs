@@ -0,0 +1,421 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+//! Function-pointer based command descriptors
+//!
+//! Instead of one `AtContext` object per command, each `AtCommand` lists
+//! plain functions for the forms it supports. All handlers receive the same
+//! shared context `T` (e.g. the device state) passed at execute time.
+
+use crate::parser::{check_names, parse, AtForm};
+use crate::{parse_int, Args, AtError, AtResult, ConfigError};
+
+/// Handler for the Exec, Query and Test forms
+pub type FormFn<T> = fn(&mut T) -> AtResult<'static>;
+
+/// Handler for the Set form
+pub type SetFn<T> = fn(&mut T, Args) -> AtResult<'static>;
+
+/// Descriptor of a single AT command
+/// Forms without a handler answer `AtError::NotSupported`.
+pub struct AtCommand<T> {
+    /// Command name (e.g. "AT+LED")
+    pub name: &'static str,
+    /// Execute handler (AT+CMD)
+    pub exec: Option<FormFn<T>>,
+    /// Query handler (AT+CMD?)
+    #[cfg(feature = "query")]
+    pub query: Option<FormFn<T>>,
+    /// Test handler (AT+CMD=?), also answering `AT+CMD=<args>?` (arguments
+    /// ignored)
+    #[cfg(feature = "test")]
+    pub test: Option<FormFn<T>>,
+    /// Set handler (AT+CMD=args)
+    pub set: Option<SetFn<T>>,
+    /// Help text, returned for the Test form when no test handler is set
+    pub help: Option<&'static str>,
+    /// Argument pattern checked before the Set handler runs (see
+    /// `AtCommand::arg_spec`)
+    pub arg_spec: Option<&'static str>,
+}
+
+impl<T> AtCommand<T> {
+
+    /// Create a descriptor with no handlers
+    /// Chain `exec`, `query`, `test`, `set` and `help` to fill it, also in
+    /// `const` contexts:
+    ///
+    /// ```ignore
+    /// const LED: AtCommand<Device> = AtCommand::named("AT+LED")
+    ///     .query(led_query)
+    ///     .set(led_set)
+    ///     .help("AT+LED=<0|1>");
+    /// ```
+    pub const fn named(name: &'static str) -> Self {
+        Self {
+            name,
+            exec: None,
+            #[cfg(feature = "query")]
+            query: None,
+            #[cfg(feature = "test")]
+            test: None,
+            set: None,
+            help: None,
+            arg_spec: None,
+        }
+    }
+
+    /// Set the Execute handler
+    pub const fn exec(mut self, f: FormFn<T>) -> Self {
+        self.exec = Some(f);
+        self
+    }
+
+    /// Set the Query handler
+    #[cfg(feature = "query")]
+    pub const fn query(mut self, f: FormFn<T>) -> Self {
+        self.query = Some(f);
+        self
+    }
+
+    /// Set the Test handler
+    #[cfg(feature = "test")]
+    pub const fn test(mut self, f: FormFn<T>) -> Self {
+        self.test = Some(f);
+        self
+    }
+
+    /// Set the Set handler
+    pub const fn set(mut self, f: SetFn<T>) -> Self {
+        self.set = Some(f);
+        self
+    }
+
+    /// Set the help text
+    pub const fn help(mut self, help: &'static str) -> Self {
+        self.help = Some(help);
+        self
+    }
+
+    /// Set the argument pattern of the Set form, checked by the parser
+    /// before the Set handler runs
+    ///
+    /// Mini-pattern grammar: comma-separated fields, each a type letter
+    /// optionally followed by `?` for an optional field:
+    /// * `n` - number, `[+-]?[0-9]+`
+    /// * `s` - string, any text (possibly empty or quoted, a quoted comma
+    ///   staying in the field as with `Args::iter_quoted`)
+    /// * `b` - bool, `0` or `1`
+    ///
+    /// `"n,n?"` accepts `AT+CMD=5` and `AT+CMD=5,7`. Optional fields must
+    /// come last; an empty optional field (`AT+CMD=5,`) counts as omitted.
+    /// An empty pattern accepts no arguments at all. Missing, extra or
+    /// mistyped fields fail with `AtError::InvalidArgs`, and so does every
+    /// Set when the pattern itself is malformed (reported by
+    /// `AtCommandParser::self_check`).
+    pub const fn arg_spec(mut self, spec: &'static str) -> Self {
+        self.arg_spec = Some(spec);
+        self
+    }
+
+    /// Check whether any form is answered: a handler or the help text
+    fn has_handler(&self) -> bool {
+        #[cfg(feature = "query")]
+        if self.query.is_some() {
+            return true;
+        }
+        #[cfg(feature = "test")]
+        if self.test.is_some() || self.help.is_some() {
+            return true;
+        }
+        self.exec.is_some() || self.set.is_some()
+    }
+}
+
+/// Parser dispatching to a table of `AtCommand` descriptors
+pub struct AtCommandParser<'a, T> {
+    /// Registered command descriptors
+    pub commands: &'a [AtCommand<T>],
+}
+
+impl<'a, T> AtCommandParser<'a, T> {
+
+    /// Create a parser over a command table
+    pub const fn new(commands: &'a [AtCommand<T>]) -> Self {
+        Self { commands }
+    }
+
+    /// Start assembling a table of at most `N` commands from handler
+    /// functions, without writing the `AtCommand` descriptors by hand:
+    ///
+    /// ```ignore
+    /// let table = AtCommandParser::<Device>::builder::<4>()
+    ///     .on_query("AT+LED", |dev| Ok(if dev.led { "1" } else { "0" }))
+    ///     .on_set("AT+LED", |dev, args| {
+    ///         dev.led = args.get_u32_in(0, 0, 1)? == 1;
+    ///         Ok("")
+    ///     })
+    ///     .on_exec("AT+RST", reset)
+    ///     .build()?;
+    /// table.parser().execute(&mut device, "AT+LED=1");
+    /// ```
+    ///
+    /// The handlers are plain function pointers, since `no_std` has no
+    /// allocator to box closures: a closure is accepted only when it
+    /// captures nothing, and every piece of state goes through the shared
+    /// context `T` passed at execute time.
+    pub fn builder<const N: usize>() -> AtCommandBuilder<T, N> {
+        AtCommandBuilder {
+            commands: [const { AtCommand::named("") }; N],
+            len: 0,
+            overflow: false,
+        }
+    }
+
+    /// Check the command table for misconfiguration, e.g. once at boot
+    ///
+    /// Runs the name checks of `AtParser::self_check` (empty, longer than
+    /// `MAX_NAME_LEN`, duplicated: only the first is reachable), then
+    /// checks that every command has at least one handler (the help text
+    /// counts as the Test handler; a command with none answers
+    /// `NotSupported` to every form) and that every argument pattern is
+    /// well-formed (see `AtCommand::arg_spec`).
+    /// Read-only; the first problem found, in table order, is returned.
+    ///
+    /// # Returns
+    /// * `Ok(())` - The table is consistent
+    /// * `Err(ConfigError)` - The first misconfiguration found
+    pub fn self_check(&self) -> Result<(), ConfigError<'static>> {
+        check_names(|| self.commands.iter().map(|c| c.name))?;
+        if let Some(command) = self.commands.iter().find(|c| !c.has_handler()) {
+            return Err(ConfigError::NoHandler(command.name));
+        }
+        match self.commands.iter().find(|c| c.arg_spec.is_some_and(|spec| !arg_spec_is_valid(spec))) {
+            Some(command) => Err(ConfigError::InvalidArgSpec(command.name)),
+            None => Ok(()),
+        }
+    }
+
+    /// Parse and execute an AT command string
+    ///
+    /// # Arguments
+    /// * `ctx` - Shared context passed to the handler
+    /// * `input` - The raw AT command string (e.g., "AT+LED=1")
+    ///
+    /// # Returns
+    /// * `Ok(&str)` - Success response from the command handler
+    /// * `Err(AtError)` - Error if parsing fails, the command is not found or
+    ///   the form has no handler
+    pub fn execute(&self, ctx: &mut T, input: &str) -> AtResult<'static> {
+        let (name, form) = parse(input)?;
+
+        let command = self.commands
+            .iter()
+            .find(|c| c.name == name)
+            .ok_or(AtError::UnknownCommand)?;
+
+        match form {
+            AtForm::Exec => command.exec.ok_or(AtError::NotSupported)?(ctx),
+            #[cfg(feature = "query")]
+            AtForm::Query => command.query.ok_or(AtError::NotSupported)?(ctx),
+            #[cfg(feature = "test")]
+            AtForm::Test | AtForm::TestWith(_) => match (command.test, command.help) {
+                (Some(test), _) => test(ctx),
+                (None, Some(help)) => Ok(help),
+                (None, None) => Err(AtError::NotSupported),
+            },
+            AtForm::Set(args) => {
+                let set = command.set.ok_or(AtError::NotSupported)?;
+                if let Some(spec) = command.arg_spec {
+                    check_arg_spec(spec, args)?;
+                }
+                set(ctx, args)
+            }
+        }
+    }
+}
+
+/// Split an argument pattern field into its type letter and optional flag
+fn spec_field(field: &str) -> (&str, bool) {
+    match field.strip_suffix('?') {
+        Some(kind) => (kind, true),
+        None => (field, false),
+    }
+}
+
+/// Check the grammar of an argument pattern (see `AtCommand::arg_spec`)
+fn arg_spec_is_valid(spec: &str) -> bool {
+    if spec.is_empty() {
+        return true;
+    }
+    let mut optional_seen = false;
+    spec.split(',').all(|field| {
+        let (kind, optional) = spec_field(field);
+        let valid = matches!(kind, "n" | "s" | "b") && (optional || !optional_seen);
+        optional_seen |= optional;
+        valid
+    })
+}
+
+/// Check Set arguments against an argument pattern
+fn check_arg_spec(spec: &str, args: Args) -> Result<(), AtError> {
+    if !arg_spec_is_valid(spec) {
+        return Err(AtError::InvalidArgs);
+    }
+    // Quoted commas do not split fields; an empty payload has none
+    let mut fields = args.iter_quoted().take(if args.is_empty() { 0 } else { usize::MAX });
+    for field in spec.split(',').filter(|f| !f.is_empty()) {
+        let (kind, optional) = spec_field(field);
+        let value = match fields.next() {
+            Some("") | None if optional => continue,
+            Some(value) => value,
+            None => return Err(AtError::InvalidArgs),
+        };
+        let valid = match kind {
+            "n" => parse_int(value).is_ok(),
+            "b" => matches!(value, "0" | "1"),
+            _ => true,
+        };
+        if !valid {
+            return Err(AtError::InvalidArgs);
+        }
+    }
+    match fields.next() {
+        Some(_) => Err(AtError::InvalidArgs),
+        None => Ok(()),
+    }
+}
+
+/// Builder of a fixed-capacity command table (see `AtCommandParser::builder`)
+///
+/// Each `on_*` call adds the handler to the command with that name,
+/// creating it on first use, so the forms of a command can be given in any
+/// order. A later handler for the same form replaces the earlier one.
+pub struct AtCommandBuilder<T, const N: usize> {
+    /// Command descriptors, the first `len` in use
+    commands: [AtCommand<T>; N],
+    /// Number of commands in use
+    len: usize,
+    /// More than `N` commands were given
+    overflow: bool,
+}
+
+impl<T, const N: usize> AtCommandBuilder<T, N> {
+
+    /// Set the Execute handler of `name`
+    pub fn on_exec(mut self, name: &'static str, f: FormFn<T>) -> Self {
+        if let Some(command) = self.entry(name) {
+            command.exec = Some(f);
+        }
+        self
+    }
+
+    /// Set the Query handler of `name`
+    #[cfg(feature = "query")]
+    pub fn on_query(mut self, name: &'static str, f: FormFn<T>) -> Self {
+        if let Some(command) = self.entry(name) {
+            command.query = Some(f);
+        }
+        self
+    }
+
+    /// Set the Test handler of `name`
+    #[cfg(feature = "test")]
+    pub fn on_test(mut self, name: &'static str, f: FormFn<T>) -> Self {
+        if let Some(command) = self.entry(name) {
+            command.test = Some(f);
+        }
+        self
+    }
+
+    /// Set the Set handler of `name`
+    pub fn on_set(mut self, name: &'static str, f: SetFn<T>) -> Self {
+        if let Some(command) = self.entry(name) {
+            command.set = Some(f);
+        }
+        self
+    }
+
+    /// Set the help text of `name`
+    pub fn help(mut self, name: &'static str, help: &'static str) -> Self {
+        if let Some(command) = self.entry(name) {
+            command.help = Some(help);
+        }
+        self
+    }
+
+    /// Set the argument pattern of `name` (see `AtCommand::arg_spec`)
+    pub fn arg_spec(mut self, name: &'static str, spec: &'static str) -> Self {
+        if let Some(command) = self.entry(name) {
+            command.arg_spec = Some(spec);
+        }
+        self
+    }
+
+    /// Finish the table
+    ///
+    /// # Returns
+    /// * `Ok(AtCommandTable)` - The assembled table
+    /// * `Err(AtError::BufferFull)` - More than `N` distinct names were given
+    pub fn build(self) -> Result<AtCommandTable<T, N>, AtError> {
+        if self.overflow {
+            return Err(AtError::BufferFull);
+        }
+        Ok(AtCommandTable { commands: self.commands, len: self.len })
+    }
+
+    /// Get the descriptor named `name`, creating it if there is room
+    fn entry(&mut self, name: &'static str) -> Option<&mut AtCommand<T>> {
+        let index = match self.commands[..self.len].iter().position(|c| c.name == name) {
+            Some(index) => index,
+            None if self.len < N => {
+                self.commands[self.len] = AtCommand::named(name);
+                self.len += 1;
+                self.len - 1
+            }
+            None => {
+                self.overflow = true;
+                return None;
+            }
+        };
+        Some(&mut self.commands[index])
+    }
+}
+
+/// Command table assembled by `AtCommandBuilder`
+pub struct AtCommandTable<T, const N: usize> {
+    /// Command descriptors, the first `len` in use
+    commands: [AtCommand<T>; N],
+    /// Number of commands in use
+    len: usize,
+}
+
+impl<T, const N: usize> AtCommandTable<T, N> {
+
+    /// Get the assembled descriptors, in the order their names first appeared
+    pub fn commands(&self) -> &[AtCommand<T>] {
+        &self.commands[..self.len]
+    }
+
+    /// Create a parser over the table
+    pub fn parser(&self) -> AtCommandParser<'_, T> {
+        AtCommandParser::new(self.commands())
+    }
+}
@@ -17,6 +17,12 @@
  *
  ***************************************************************************/
  
+#[cfg(feature = "test")]
+use core::fmt::Write;
+#[cfg(feature = "async")]
+use core::future::Future;
+
+use crate::parser::{AtForm, FormKind};
 use crate::{Args, AtError, AtResult};
 
 /// Trait that defines the context for AT command execution.
@@ -31,20 +37,270 @@ pub trait AtContext {
 
     /// Query command (AT+CMD?)
     /// This is called to retrieve the current value/state of a command.
+    /// Only available with the `query` feature.
+    #[cfg(feature = "query")]
     fn query(&mut self) -> AtResult<'static> {
         Err(AtError::NotSupported)
     }
     
+    /// Multi-row query command (AT+CMD?)
+    /// Used by the framed output path to emit a variable number of rows
+    /// (e.g. scan results), each on its own line before the terminator.
+    /// By default yields the single `query` result.
+    #[cfg(feature = "query")]
+    fn query_iter(&mut self) -> impl Iterator<Item = AtResult<'static>>
+    where
+        Self: Sized {
+        core::iter::once(self.query())
+    }
+
     /// Test command (AT+CMD=?)
     /// This is called to check if a command is supported or to get valid parameter ranges.
+    /// Only available with the `test` feature.
+    #[cfg(feature = "test")]
     fn test(&mut self) -> AtResult<'static> {
         Err(AtError::NotSupported)
     }
 
+    /// Test command with a parameter (AT+CMD=<args>?), a capability query
+    /// about one value (e.g. the ranges of a given mode)
+    /// Only numeric arguments make this form: other text ending with `?`
+    /// reaches `set`.
+    /// By default ignores the arguments and delegates to `test`. Dispatched
+    /// context-free by `execute_with`; indexed entries answer `NotSupported`.
+    #[cfg(feature = "test")]
+    fn test_with(&mut self, _args: Args) -> AtResult<'static> {
+        self.test()
+    }
+
+    /// Test command (AT+CMD=?) written straight to the framed output
+    ///
+    /// Lets the answer be formatted at render time, e.g. with
+    /// `response::write_template` to fill configurable limits in. Write the
+    /// payload lines with their `\r\n`; the parser adds the terminator.
+    /// The framed paths call this first and use `test` when it returns
+    /// `NotSupported` (the default); the plain `execute` path always uses
+    /// `test`. Writer failures should be reported as `AtError::BufferFull`.
+    #[cfg(feature = "test")]
+    fn test_into(&mut self, _out: &mut dyn Write) -> Result<(), AtError> {
+        Err(AtError::NotSupported)
+    }
+
+    /// Binary payload of any form (e.g. `AT+FREAD=log,0,64` reading a file)
+    ///
+    /// Called by the framed paths before the form handlers; `NotSupported`
+    /// (the default) falls through to them, any other error is reported as
+    /// usual. `validate_set` is not run first. `execute_into` copies the
+    /// bytes verbatim, so they may hold anything including `\r\n`: the host
+    /// must know the length (e.g. from the request) to find the line end and
+    /// `OK` that follow. `execute_framed` and `execute_line` write to text
+    /// and report non-UTF-8 payloads as `AtError::InvalidEncoding`. The plain
+    /// `execute` path never calls this. Not called for indexed entries.
+    fn bytes(&mut self, _form: &AtForm) -> Result<&'static [u8], AtError> {
+        Err(AtError::NotSupported)
+    }
+
     /// Set command (AT+CMD=args)
     /// This is called to set parameters for a command.
-    fn set(&mut self, _args: Args) -> AtResult<'static> {
+    /// By default delegates to `set_void` and answers with no payload.
+    fn set(&mut self, args: Args) -> AtResult<'static> {
+        self.set_void(args).map(|()| "")
+    }
+
+    /// Check a Set before it is applied (AT+CMD=args)
+    ///
+    /// The parser always calls this first and only calls `set` (or
+    /// `set_ctx`) when it returns `Ok(())`, so validation errors leave the
+    /// module untouched. Commands updating several coupled fields should
+    /// check every field here and keep `set` infallible, making the Set
+    /// all-or-nothing. Defaults to `Ok(())`. Not called for indexed entries.
+    fn validate_set(&self, _args: Args) -> Result<(), AtError> {
+        Ok(())
+    }
+
+    /// Maximum number of arguments accepted by the Set form
+    /// Only enforced by parsers with strict arguments enabled
+    /// (`AtParser::set_strict_args`); `None` (default) means unchecked.
+    fn arity(&self) -> Option<usize> {
+        None
+    }
+
+    /// Intermediate result code opening a data phase (e.g. `"CONNECT"`)
+    ///
+    /// Asked by the framed paths after every successful Exec or Set with a
+    /// text or empty response, never after a Query or Test; `Some` makes
+    /// the parser write the code instead of `OK` and route the following
+    /// bytes to `data` (see `AtParser::feed_data`). Commands that start a
+    /// data session remember it in their handler and report it here once.
+    /// Defaults to `None`.
+    fn data_phase(&mut self) -> Option<&'static str> {
+        None
+    }
+
+    /// Bytes received during the data phase opened by `data_phase`
+    /// Return `None` to keep receiving, or the final result to end the phase
+    /// (its payload and terminator are written then). By default the phase
+    /// ends at once with `NotSupported`.
+    fn data(&mut self, _bytes: &[u8]) -> Option<AtResult<'static>> {
+        Some(Err(AtError::NotSupported))
+    }
+
+    /// Mark the command as read-only (e.g. a firmware version or a sensor
+    /// reading)
+    ///
+    /// When `true`, the parser rejects the Set form with `AtError::ReadOnly`
+    /// before `validate_set` and `set` run, telling the host the value
+    /// cannot be written. With the default `false`, a missing Set handler
+    /// answers the generic `NotSupported`, which does not say whether the
+    /// form is unknown or forbidden. Only checked through the parser
+    /// (`AtParser::validate` included), indexed entries included.
+    fn read_only(&self) -> bool {
+        false
+    }
+
+    /// Device states in which the command is accepted, bit `n` enabling
+    /// state `n` (see `AtParser::set_state`)
+    /// Defaults to `u32::MAX`, every state.
+    fn state_mask(&self) -> u32 {
+        u32::MAX
+    }
+
+    /// One-line description of the command, empty by default
+    /// Reported by `AtParser::describe_into` for host tooling.
+    fn help(&self) -> &'static str {
+        ""
+    }
+
+    /// Set command without payload (AT+CMD=args)
+    /// Override this instead of `set` when the command only reports success:
+    /// `Ok(())` becomes an empty response, which the framed output renders
+    /// as the success terminator alone (`OK`).
+    fn set_void(&mut self, _args: Args) -> Result<(), AtError> {
+        Err(AtError::NotSupported)
+    }
+
+    /// Execute command of an indexed entry (AT+SOCK1 for "AT+SOCK#")
+    /// `index` is the numeric suffix sent by the host; reject out-of-range
+    /// instances with `AtError::InvalidArgs`.
+    fn exec_indexed(&mut self, _index: u32) -> AtResult<'static> {
+        Err(AtError::NotSupported)
+    }
+
+    /// Query command of an indexed entry (AT+SOCK1?)
+    #[cfg(feature = "query")]
+    fn query_indexed(&mut self, _index: u32) -> AtResult<'static> {
+        Err(AtError::NotSupported)
+    }
+
+    /// Test command of an indexed entry (AT+SOCK1=?)
+    #[cfg(feature = "test")]
+    fn test_indexed(&mut self, _index: u32) -> AtResult<'static> {
+        Err(AtError::NotSupported)
+    }
+
+    /// Set command of an indexed entry (AT+SOCK1=args)
+    fn set_indexed(&mut self, _index: u32, _args: Args) -> AtResult<'static> {
         Err(AtError::NotSupported)
     }
 
-}
\ No newline at end of file
+}
+
+/// Extension of `AtContext` for handlers that need a shared context `C`
+/// (a UART handle, a clock...) passed by `AtParser::execute_with`.
+///
+/// Every method defaults to its context-free `AtContext` counterpart, so
+/// simple modules only need an empty `impl AtContextWith<C> for Module {}`.
+pub trait AtContextWith<C: ?Sized>: AtContext {
+
+    /// Execute command (AT+CMD) with the shared context
+    fn exec_ctx(&self, _ctx: &mut C) -> AtResult<'static> {
+        self.exec()
+    }
+
+    /// Query command (AT+CMD?) with the shared context
+    #[cfg(feature = "query")]
+    fn query_ctx(&mut self, _ctx: &mut C) -> AtResult<'static> {
+        self.query()
+    }
+
+    /// Test command (AT+CMD=?) with the shared context
+    #[cfg(feature = "test")]
+    fn test_ctx(&mut self, _ctx: &mut C) -> AtResult<'static> {
+        self.test()
+    }
+
+    /// Set command (AT+CMD=args) with the shared context
+    fn set_ctx(&mut self, args: Args, _ctx: &mut C) -> AtResult<'static> {
+        self.set(args)
+    }
+
+}
+
+/// Catch-all handler for commands that match no registered name
+/// (see `AtParser::set_fallback`), e.g. to forward them to a peer chip.
+pub trait AtFallback {
+
+    /// Handle an unmatched command
+    ///
+    /// # Arguments
+    /// * `input` - The whole trimmed input line (e.g. "AT+PEER=1,2")
+    /// * `name` - The command name as sent (e.g. "AT+PEER")
+    /// * `form` - The parsed command form
+    fn handle(&mut self, input: &str, name: &str, form: FormKind) -> AtResult<'static>;
+
+}
+
+/// Async counterpart of `AtContext` for executors such as embassy.
+/// Handlers may await I/O (UART, network...) before answering.
+/// Only available with the `async` feature; dispatched by `AtParser::execute_async`.
+#[cfg(feature = "async")]
+pub trait AsyncAtContext {
+
+    /// Execute command (AT+CMD)
+    fn exec(&mut self) -> impl Future<Output = AtResult<'static>> {
+        async { Err(AtError::NotSupported) }
+    }
+
+    /// Query command (AT+CMD?)
+    #[cfg(feature = "query")]
+    fn query(&mut self) -> impl Future<Output = AtResult<'static>> {
+        async { Err(AtError::NotSupported) }
+    }
+
+    /// Test command (AT+CMD=?)
+    #[cfg(feature = "test")]
+    fn test(&mut self) -> impl Future<Output = AtResult<'static>> {
+        async { Err(AtError::NotSupported) }
+    }
+
+    /// Test command with a parameter (AT+CMD=<args>?)
+    /// By default ignores the arguments and delegates to `test`.
+    #[cfg(feature = "test")]
+    fn test_with(&mut self, _args: Args) -> impl Future<Output = AtResult<'static>> {
+        self.test()
+    }
+
+    /// Set command (AT+CMD=args)
+    fn set(&mut self, _args: Args) -> impl Future<Output = AtResult<'static>> {
+        async { Err(AtError::NotSupported) }
+    }
+
+    /// Maximum number of arguments accepted by the Set form, checked like
+    /// `AtContext::arity` by parsers with strict arguments enabled
+    fn arity(&self) -> Option<usize> {
+        None
+    }
+
+    /// Reject the Set form with `AtError::ReadOnly`, like
+    /// `AtContext::read_only`
+    fn read_only(&self) -> bool {
+        false
+    }
+
+    /// Device states in which the command is accepted, like
+    /// `AtContext::state_mask`
+    fn state_mask(&self) -> u32 {
+        u32::MAX
+    }
+
+}
@@ -0,0 +1,109 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+//! Command execution log for post-mortem debugging (requires the `history` feature)
+
+/// One recorded command execution
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HistoryEntry<'n> {
+    /// Registered command name, `None` for unknown or malformed commands
+    pub name: Option<&'n str>,
+    /// Result code: 0 on success, `AtError::code()` otherwise
+    pub code: u16,
+}
+
+/// Storage for recorded commands, attached with `AtParser::set_history`
+/// `'n` is the lifetime of the registered command names.
+pub trait History<'n> {
+
+    /// Record a new entry, evicting the oldest one when full
+    fn record(&mut self, entry: HistoryEntry<'n>);
+
+    /// Number of stored entries
+    fn len(&self) -> usize;
+
+    /// Check if no entry is stored
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get a stored entry, index 0 being the oldest
+    fn get(&self, index: usize) -> Option<HistoryEntry<'n>>;
+
+}
+
+/// Ring buffer keeping the last `N` commands, without allocation
+pub struct CommandHistory<'n, const N: usize> {
+    /// Entry storage, only the first `len` slots (in ring order) are valid
+    entries: [HistoryEntry<'n>; N],
+    /// Index of the oldest entry
+    head: usize,
+    /// Number of valid entries
+    len: usize,
+}
+
+impl<'n, const N: usize> CommandHistory<'n, N> {
+
+    /// Create an empty log
+    pub const fn new() -> Self {
+        Self {
+            entries: [HistoryEntry { name: None, code: 0 }; N],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// Forget every recorded entry
+    pub fn clear(&mut self) {
+        self.head = 0;
+        self.len = 0;
+    }
+}
+
+impl<'n, const N: usize> Default for CommandHistory<'n, N> {
+
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'n, const N: usize> History<'n> for CommandHistory<'n, N> {
+
+    fn record(&mut self, entry: HistoryEntry<'n>) {
+        if N == 0 {
+            return;
+        }
+        if self.len < N {
+            self.entries[(self.head + self.len) % N] = entry;
+            self.len += 1;
+        } else {
+            self.entries[self.head] = entry;
+            self.head = (self.head + 1) % N;
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn get(&self, index: usize) -> Option<HistoryEntry<'n>> {
+        (index < self.len).then(|| self.entries[(self.head + index) % N])
+    }
+}
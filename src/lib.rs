@@ -21,12 +21,13 @@
 //!
 //! This library provides a flexible parser for AT commands, commonly used in
 //! embedded systems and communication devices. It supports no_std environments.
+//!
+//! The crate never allocates and does not link `alloc`: no feature needs a
+//! global allocator (`heapless` collections live on the stack), so the
+//! `no_std` builds work on allocator-free targets.
 
 #![cfg_attr(any(feature = "enable_panic", feature = "osal_rs"), no_std)]
 
-#[cfg(any(feature = "enable_panic", feature = "osal_rs"))]
-extern crate alloc;
-
 #[cfg(feature = "osal_rs")]
 extern crate osal_rs;
 
@@ -42,12 +43,25 @@ fn panic(_info: &core::panic::PanicInfo) -> ! {
     loop {}
 }
 
+pub mod buffered;
+pub mod command;
 pub mod context;
+#[cfg(feature = "history")]
+pub mod history;
+pub mod line;
 pub mod parser;
+pub mod registers;
+#[cfg(feature = "linker_registry")]
+pub mod registry;
+pub mod response;
+#[cfg(feature = "osal_rs")]
+pub mod sync;
+pub mod table;
+pub mod urc;
 
 
 /// Error types that can occur during AT command processing
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AtError {
     /// The command is not recognized
     UnknownCommand,
@@ -55,13 +69,166 @@ pub enum AtError {
     NotSupported,
     /// The command arguments are invalid
     InvalidArgs,
+    /// The input line exceeds the parser's maximum line length
+    /// Produced by `AtParser::execute_bytes`
+    LineTooLong,
+    /// The response does not fit in the caller-provided buffer
+    /// Produced by `AtParser::execute_into`, and by `AtParser::add_group`
+    /// and `AtParser::add_remap` when no slot is left
+    BufferFull,
+    /// The input is not valid UTF-8
+    /// Produced by `AtParser::execute_bytes`
+    InvalidEncoding,
+    /// A mandatory argument is absent
+    /// Produced by `Args::require`
+    MissingArg,
+    /// The line is malformed; the value is the byte offset where parsing
+    /// failed, relative to the trimmed (and normalized, see
+    /// `AtParser::set_normalizer`) input, e.g. the segment for
+    /// `AtParser::execute_line`. Callers can skip from there to the next
+    /// `;` or newline to resync.
+    SyntaxAt(usize),
+    /// A previous long-running operation is still in progress; the command
+    /// was not dispatched
+    /// Produced while `AtParser::set_busy` is on
+    Busy,
+    /// The command is read-only: its Set form is rejected
+    /// Produced for commands whose `AtContext::read_only` is `true`, while
+    /// `NotSupported` covers every other missing form
+    ReadOnly,
+    /// Domain-specific failure described by a static text, e.g.
+    /// `Message("sensor not calibrated")`
+    /// Rendered as is by `Display`, and as `ERROR: <message>` by the framed
+    /// paths (unless an error formatter is installed). Allocation-free.
+    Message(&'static str),
+}
+
+impl AtError {
+    /// Get the numeric code of this error, suitable for numeric result
+    /// styles such as `+CME ERROR: <code>`
+    /// Every `Message` shares the generic code 100 (`+CME ERROR` "unknown").
+    pub fn code(&self) -> u16 {
+        match self {
+            AtError::UnknownCommand => 1,
+            AtError::NotSupported => 2,
+            AtError::InvalidArgs => 3,
+            AtError::LineTooLong => 4,
+            AtError::BufferFull => 5,
+            AtError::InvalidEncoding => 6,
+            AtError::MissingArg => 7,
+            AtError::SyntaxAt(_) => 8,
+            AtError::Busy => 9,
+            AtError::ReadOnly => 10,
+            AtError::Message(_) => 100,
+        }
+    }
+}
+
+impl core::fmt::Display for AtError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let text = match self {
+            AtError::UnknownCommand => "Unknown command",
+            AtError::NotSupported => "Not supported",
+            AtError::InvalidArgs => "Invalid arguments",
+            AtError::LineTooLong => "Line too long",
+            AtError::BufferFull => "Buffer full",
+            AtError::InvalidEncoding => "Invalid encoding",
+            AtError::MissingArg => "Missing argument",
+            AtError::SyntaxAt(offset) => return write!(f, "Syntax error at byte {}", offset),
+            AtError::Busy => "Busy",
+            AtError::ReadOnly => "Read-only",
+            AtError::Message(message) => message,
+        };
+        f.write_str(text)
+    }
+}
+
+/// Misconfiguration of a command table, reported by
+/// `AtParser::self_check` and `AtCommandParser::self_check`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigError<'a> {
+    /// A command is registered with an empty name
+    EmptyName,
+    /// The name is registered more than once
+    DuplicateName(&'a str),
+    /// The name is longer than `parser::MAX_NAME_LEN` (or the limit given
+    /// to `AtParser::set_commands_checked`)
+    NameTooLong(&'a str),
+    /// The command has no handler for any form
+    NoHandler(&'a str),
+    /// The argument pattern of the command is malformed (see
+    /// `AtCommand::arg_spec`)
+    InvalidArgSpec(&'a str),
+    /// A name handed over from C is not valid UTF-8 (see
+    /// `AtParser::from_sentinel_table`)
+    InvalidName,
+    /// The table has more entries than the storage provided for them
+    TableFull,
+}
+
+impl core::fmt::Display for ConfigError<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ConfigError::EmptyName => f.write_str("Empty command name"),
+            ConfigError::DuplicateName(name) => write!(f, "Duplicate command {}", name),
+            ConfigError::NameTooLong(name) => write!(f, "Command name too long: {}", name),
+            ConfigError::NoHandler(name) => write!(f, "No handler for {}", name),
+            ConfigError::InvalidArgSpec(name) => write!(f, "Invalid argument pattern for {}", name),
+            ConfigError::InvalidName => f.write_str("Command name is not UTF-8"),
+            ConfigError::TableFull => f.write_str("Command table full"),
+        }
+    }
 }
 
 /// Result type for AT command operations
 /// Returns either a static string response or an AtError
 pub type AtResult<'a> = Result<&'a str, AtError>;
 
+/// Payload of a successful command as rendered by the framed output path
+/// (see `AtParser::execute_framed`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Response<'a> {
+    /// No payload: only the final `OK` terminator is emitted
+    None,
+    /// Payload line emitted before the final `OK` terminator
+    Text(&'a str),
+    /// Raw binary payload (see `AtContext::bytes`), written as-is before the
+    /// `\r\n` line end and the final `OK` terminator
+    Bytes(&'a [u8]),
+    /// The handler wrote its own output: nothing is emitted, not even the
+    /// terminator (see `SUPPRESSED`)
+    Suppressed,
+    /// The command opened a data phase: this intermediate result code (e.g.
+    /// `CONNECT`) replaces the terminator (see `AtParser::feed_data`)
+    Intermediate(&'a str),
+}
+
+/// Payload a handler returns to suppress the framed response entirely
+///
+/// For commands with a bespoke output protocol (e.g. a firmware update
+/// streaming its progress): `execute_framed` then writes neither a payload
+/// nor `OK`, and a `;` batch goes on with the next command, its final `OK`
+/// still closing the whole line. The suppressed command is a success, so
+/// the error formatter, the response prefix and the response filter never
+/// apply to it. The plain `execute`, `execute_with` and `execute_async`
+/// paths return it as an empty success, `Ok("")`.
+pub const SUPPRESSED: &str = "\0SUPPRESSED";
+
+impl<'a> From<&'a str> for Response<'a> {
+    /// Convert a handler payload into a response
+    /// An empty payload means "terminator only" and maps to `Response::None`,
+    /// the `SUPPRESSED` marker maps to `Response::Suppressed`
+    fn from(payload: &'a str) -> Self {
+        match payload {
+            "" => Response::None,
+            SUPPRESSED => Response::Suppressed,
+            payload => Response::Text(payload),
+        }
+    }
+}
+
 /// Structure holding the arguments passed to an AT command
+#[derive(Clone, Copy)]
 pub struct Args<'a> {
     /// Raw argument string (comma-separated values)
     pub raw: &'a str,
@@ -70,9 +237,561 @@ pub struct Args<'a> {
 impl<'a> Args<'a> {
     /// Get an argument by index (0-based)
     /// Arguments are separated by commas
+    /// An empty payload yields `Some("")` for index 0 (see `is_empty`)
     pub fn get(&self, index: usize) -> Option<&'a str> {
         self.raw.split(',').nth(index)
     }
+
+    /// Iterate over all arguments in order
+    pub fn iter(&self) -> impl Iterator<Item = &'a str> + use<'a> {
+        self.raw.split(',')
+    }
+
+    /// Iterate over all arguments in order, honoring double-quoted strings
+    /// (e.g. `"+15551234",145` for `AT+CMGS`)
+    ///
+    /// Commas inside double quotes do not split fields, and a field
+    /// enclosed in double quotes is yielded without them; anything inside
+    /// the quotes, `+` included, is kept verbatim. An unterminated quote runs
+    /// to the end of the payload and keeps its opening `"`. Unlike `iter`,
+    /// which splits at every comma and keeps the quotes.
+    pub fn iter_quoted(&self) -> impl Iterator<Item = &'a str> + use<'a> {
+        let mut rest = Some(self.raw);
+        core::iter::from_fn(move || {
+            let field = rest?;
+            let mut quoted = false;
+            let end = field.bytes().position(|b| {
+                if b == b'"' {
+                    quoted = !quoted;
+                }
+                b == b',' && !quoted
+            });
+            let field = match end {
+                Some(end) => {
+                    rest = Some(&field[end + 1..]);
+                    &field[..end]
+                }
+                None => {
+                    rest = None;
+                    field
+                }
+            };
+            Some(field
+                .strip_prefix('"')
+                .and_then(|f| f.strip_suffix('"'))
+                .unwrap_or(field))
+        })
+    }
+
+    /// Get an argument by index (0-based), honoring double-quoted strings
+    /// (see `iter_quoted`)
+    pub fn get_quoted(&self, index: usize) -> Option<&'a str> {
+        self.iter_quoted().nth(index)
+    }
+
+    /// Iterate over all arguments in order, each with its byte offset
+    /// (e.g. to report "error at column N")
+    /// Offsets are relative to the argument string `raw`, not to the
+    /// command line: add the length of the name and delimiter (`AT+CMD=`)
+    /// to point into the line.
+    pub fn iter_offsets(&self) -> impl Iterator<Item = (usize, &'a str)> + use<'a> {
+        self.iter().scan(0, |offset, field| {
+            let start = *offset;
+            *offset += field.len() + 1;
+            Some((start, field))
+        })
+    }
+
+    /// Check the number of arguments against the inclusive range `min..=max`
+    /// An empty payload counts as zero arguments.
+    ///
+    /// # Returns
+    /// * `Err(AtError::MissingArg)` - Fewer than `min` arguments
+    /// * `Err(AtError::InvalidArgs)` - More than `max` arguments
+    pub fn expect(&self, min: usize, max: usize) -> Result<(), AtError> {
+        let count = self.len();
+        if count < min {
+            Err(AtError::MissingArg)
+        } else if count > max {
+            Err(AtError::InvalidArgs)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Number of arguments, an empty payload counting as zero
+    pub fn len(&self) -> usize {
+        if self.raw.is_empty() {
+            0
+        } else {
+            self.iter().count()
+        }
+    }
+
+    /// Check if the argument payload is empty
+    ///
+    /// `AT+CMD=` (trailing `=` without value) is a Set with empty arguments,
+    /// conventionally meaning "clear", while `AT+CMD` is an Exec. Note that
+    /// `get(0)` still returns `Some("")` for an empty payload, so use this
+    /// method to tell the two cases apart.
+    pub fn is_empty(&self) -> bool {
+        self.raw.is_empty()
+    }
+
+    /// Get a mandatory argument by index (0-based)
+    /// Returns `AtError::MissingArg` when the argument is absent
+    pub fn require(&self, index: usize) -> Result<&'a str, AtError> {
+        self.get(index).ok_or(AtError::MissingArg)
+    }
+
+    /// Check whether an argument equals `expected`, ignoring ASCII case
+    /// (e.g. `args.matches(0, "on")` accepts `ON` and `On`)
+    /// Returns `false` when the argument is absent.
+    pub fn matches(&self, index: usize, expected: &str) -> bool {
+        self.get(index).is_some_and(|field| field.eq_ignore_ascii_case(expected))
+    }
+
+    /// Get an argument by index parsed as `u32`
+    ///
+    /// Accepted grammar: `[+-]?[0-9]+`, without surrounding whitespace.
+    /// Leading zeros are allowed (`007` is 7) and `-0` is 0.
+    ///
+    /// # Returns
+    /// * `Err(AtError::MissingArg)` - The argument is absent
+    /// * `Err(AtError::InvalidArgs)` - The argument is malformed or out of range
+    pub fn get_u32(&self, index: usize) -> Result<u32, AtError> {
+        let value = parse_int(self.require(index)?)?;
+        u32::try_from(value).map_err(|_| AtError::InvalidArgs)
+    }
+
+    /// Get an argument by index parsed as `i32`
+    ///
+    /// Same grammar as `get_u32`: `+5` is 5, `-007` is -7.
+    ///
+    /// # Returns
+    /// * `Err(AtError::MissingArg)` - The argument is absent
+    /// * `Err(AtError::InvalidArgs)` - The argument is malformed or out of range
+    pub fn get_i32(&self, index: usize) -> Result<i32, AtError> {
+        let value = parse_int(self.require(index)?)?;
+        i32::try_from(value).map_err(|_| AtError::InvalidArgs)
+    }
+
+    /// Parse every argument as `u32` into `out`, for variadic commands
+    /// (e.g. `AT+WRITE=1,2,3,4`)
+    ///
+    /// Same grammar as `get_u32`; an empty payload has no values. More
+    /// values than `out` holds is an error, never a silent truncation: the
+    /// host sent more than the command accepts. On error the values parsed
+    /// so far are left in `out`.
+    ///
+    /// # Returns
+    /// * `Ok(usize)` - Number of values written to the front of `out`
+    /// * `Err(AtError::InvalidArgs)` - A field is malformed or out of range,
+    ///   or there are more fields than `out.len()`
+    pub fn parse_all_u32(&self, out: &mut [u32]) -> Result<usize, AtError> {
+        if self.is_empty() {
+            return Ok(0);
+        }
+        let mut count = 0;
+        for field in self.iter() {
+            let slot = out.get_mut(count).ok_or(AtError::InvalidArgs)?;
+            *slot = u32::try_from(parse_int(field)?).map_err(|_| AtError::InvalidArgs)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Get an argument by index parsed as `u32` and checked against the
+    /// inclusive range `min..=max`
+    ///
+    /// If `min > max` the range is empty and every value is rejected.
+    ///
+    /// # Returns
+    /// * `Err(AtError::MissingArg)` - The argument is absent
+    /// * `Err(AtError::InvalidArgs)` - The argument is malformed or out of range
+    pub fn get_u32_in(&self, index: usize, min: u32, max: u32) -> Result<u32, AtError> {
+        let value = self.get_u32(index)?;
+        if (min..=max).contains(&value) {
+            Ok(value)
+        } else {
+            Err(AtError::InvalidArgs)
+        }
+    }
+
+    /// Get a decimal argument as a fixed-point integer scaled by `scale`
+    ///
+    /// `scale` must be a power of ten and sets the accepted fractional
+    /// digits: at scale 100, `23.5` is 2350 and `-0.05` is -5. Grammar:
+    /// `[+-]?[0-9]+(\.[0-9]+)?`. Values are never rounded or truncated: more
+    /// fractional digits than the scale supports (`23.555` at scale 100),
+    /// a scale that is not a power of ten or a result outside `i32` are
+    /// rejected.
+    ///
+    /// # Returns
+    /// * `Err(AtError::MissingArg)` - The argument is absent
+    /// * `Err(AtError::InvalidArgs)` - The argument is malformed, too precise
+    ///   or out of range
+    pub fn get_fixed(&self, index: usize, scale: u32) -> Result<i32, AtError> {
+        let field = self.require(index)?;
+        let (int, frac) = match field.split_once('.') {
+            Some((_, "")) => return Err(AtError::InvalidArgs),
+            Some((int, frac)) => (int, frac),
+            None => (field, ""),
+        };
+        if !frac.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(AtError::InvalidArgs);
+        }
+
+        let mut digits = 0;
+        let mut unit = 1u32;
+        while unit < scale {
+            unit = unit.checked_mul(10).ok_or(AtError::InvalidArgs)?;
+            digits += 1;
+        }
+        if unit != scale || frac.len() > digits {
+            return Err(AtError::InvalidArgs);
+        }
+
+        let negative = int.starts_with('-');
+        let int = parse_int(int)?.checked_abs().ok_or(AtError::InvalidArgs)?;
+        let frac = if frac.is_empty() { 0 } else { parse_int(frac)? * 10i64.pow((digits - frac.len()) as u32) };
+        let value = int
+            .checked_mul(i64::from(scale))
+            .and_then(|v| v.checked_add(frac))
+            .ok_or(AtError::InvalidArgs)?;
+        i32::try_from(if negative { -value } else { value }).map_err(|_| AtError::InvalidArgs)
+    }
+
+    /// Get a duration argument in milliseconds (e.g. `AT+TMO=10s`)
+    ///
+    /// Grammar: `[0-9]+(ms|s|m)?`, units in lowercase for milliseconds,
+    /// seconds and minutes. A bare number is in milliseconds: `500` and
+    /// `500ms` are both 500, `10s` is 10000 and `2m` is 120000.
+    ///
+    /// # Returns
+    /// * `Err(AtError::MissingArg)` - The argument is absent
+    /// * `Err(AtError::InvalidArgs)` - The argument is malformed, has an
+    ///   unknown unit or overflows `u32` milliseconds
+    pub fn get_duration_ms(&self, index: usize) -> Result<u32, AtError> {
+        let field = self.require(index)?;
+        let digits = field.bytes().take_while(u8::is_ascii_digit).count();
+        let (value, unit) = field.split_at(digits);
+        let scale = match unit {
+            "" | "ms" => 1,
+            "s" => 1_000,
+            "m" => 60_000,
+            _ => return Err(AtError::InvalidArgs),
+        };
+        value
+            .parse::<u32>()
+            .ok()
+            .and_then(|value| value.checked_mul(scale))
+            .ok_or(AtError::InvalidArgs)
+    }
+
+    /// Get an argument by index parsed as a dotted-quad IPv4 address
+    /// (e.g. `AT+IP=192.168.1.10`)
+    ///
+    /// The field may be enclosed in double quotes (`"10.0.0.1"`). Exactly
+    /// four `.`-separated octets are required, each made of 1 to 3 digits
+    /// with a value up to 255; no sign or whitespace is accepted.
+    ///
+    /// # Returns
+    /// * `Err(AtError::MissingArg)` - The argument is absent
+    /// * `Err(AtError::InvalidArgs)` - The argument is not a valid address
+    pub fn get_ipv4(&self, index: usize) -> Result<[u8; 4], AtError> {
+        let field = self.require(index)?;
+        let field = field
+            .strip_prefix('"')
+            .and_then(|f| f.strip_suffix('"'))
+            .unwrap_or(field);
+
+        let mut octets = [0u8; 4];
+        let mut parts = field.split('.');
+        for octet in &mut octets {
+            let part = parts.next().ok_or(AtError::InvalidArgs)?;
+            if part.is_empty() || part.len() > 3 || !part.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(AtError::InvalidArgs);
+            }
+            *octet = part.parse().map_err(|_| AtError::InvalidArgs)?;
+        }
+        match parts.next() {
+            Some(_) => Err(AtError::InvalidArgs),
+            None => Ok(octets),
+        }
+    }
+
+    /// Get exactly two `u32` arguments (e.g. `AT+MOVE=10,20`)
+    ///
+    /// Strict about the shape: any other argument count, including extra
+    /// trailing fields, is `AtError::InvalidArgs`, as is a malformed value.
+    pub fn get_pair_u32(&self) -> Result<(u32, u32), AtError> {
+        if self.len() != 2 {
+            return Err(AtError::InvalidArgs);
+        }
+        Ok((self.get_u32(0)?, self.get_u32(1)?))
+    }
+
+    /// Get exactly three `u32` arguments (e.g. `AT+RGB=255,128,0`)
+    /// Same strictness as `get_pair_u32`.
+    pub fn get_triple_u32(&self) -> Result<(u32, u32, u32), AtError> {
+        if self.len() != 3 {
+            return Err(AtError::InvalidArgs);
+        }
+        Ok((self.get_u32(0)?, self.get_u32(1)?, self.get_u32(2)?))
+    }
+
+    /// Parse every argument into a tuple, e.g.
+    /// `args.parse_tuple::<(u8, bool, u32)>()` for `AT+CMD=5,1,1000`
+    ///
+    /// Fields are converted with `FromAtArg`. The argument count must match
+    /// the tuple arity exactly, otherwise `AtError::InvalidArgs` is returned;
+    /// a malformed field reports the `FromAtArg` error.
+    pub fn parse_tuple<T>(&self) -> Result<T, AtError>
+    where
+        T: FromAtArgs<'a> {
+        T::from_at_args(self)
+    }
+
+    /// Get the value of a named `key=value` field (e.g. `mode` in
+    /// `AT+CFG=mode=2,level=5`)
+    /// The field is split at its first `=`; the first field with `key`
+    /// wins. Positional fields (without `=`) never match.
+    pub fn named(&self, key: &str) -> Option<&'a str> {
+        self.iter()
+            .filter_map(|field| field.split_once('='))
+            .find(|(k, _)| *k == key)
+            .map(|(_, value)| value)
+    }
+
+    /// Iterate over the keys of the named `key=value` fields supplied, in
+    /// order, so a handler can apply only the provided settings
+    /// Positional fields (without `=`) are skipped; a key sent twice is
+    /// yielded twice.
+    pub fn present_keys(&self) -> impl Iterator<Item = &'a str> + use<'a> {
+        self.iter()
+            .filter_map(|field| field.split_once('='))
+            .map(|(key, _)| key)
+    }
+
+    /// Look up `key` in a query-string payload (`AT+HTTP="a=1&b=2"`) and
+    /// percent-decode its value into `out` (see `percent_decode`)
+    ///
+    /// The whole payload is taken as `&`-separated `key=value` pairs, with
+    /// optional surrounding double quotes; commas are part of the values.
+    /// Keys are compared as sent, without decoding, and a pair without `=`
+    /// has an empty value. The first pair with `key` wins.
+    ///
+    /// # Returns
+    /// * `Ok(Some(value))` - The decoded value, borrowing `out`
+    /// * `Ok(None)` - No pair has this key
+    /// * `Err(AtError::InvalidArgs)` - The value is malformed percent-encoding
+    /// * `Err(AtError::BufferFull)` - The decoded value does not fit in `out`
+    pub fn query_param<'o>(&self, key: &str, out: &'o mut [u8]) -> Result<Option<&'o str>, AtError> {
+        let query = self.raw
+            .strip_prefix('"')
+            .and_then(|q| q.strip_suffix('"'))
+            .unwrap_or(self.raw);
+        let value = query
+            .split('&')
+            .map(|pair| pair.split_once('=').unwrap_or((pair, "")))
+            .find(|(k, _)| *k == key);
+        match value {
+            Some((_, value)) => percent_decode(value, out).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Run an external parser over the raw payload (see `raw_payload`),
+    /// e.g. a `nom` combinator for a richer argument grammar
+    ///
+    /// Any error of `f` is reported as `AtError::InvalidArgs`, so the crate
+    /// does not depend on the parser library:
+    ///
+    /// ```ignore
+    /// let (r, g, b) = args.parse_with(|raw| {
+    ///     all_consuming(tuple((hex_u8, hex_u8, hex_u8)))(raw).map(|(_, rgb)| rgb)
+    /// })?;
+    /// ```
+    pub fn parse_with<F, O, E>(&self, f: F) -> Result<O, AtError>
+    where
+        F: FnOnce(&'a str) -> Result<O, E> {
+        f(self.raw).map_err(|_| AtError::InvalidArgs)
+    }
+
+    /// Get the whole raw argument payload (everything after `=`)
+    pub fn raw_payload(&self) -> &'a str {
+        self.raw
+    }
+
+    /// Get the length of the raw argument payload in bytes
+    /// Note: this counts bytes, not characters, so it can be checked
+    /// directly against fixed-size buffers
+    pub fn raw_len(&self) -> usize {
+        self.raw.len()
+    }
+}
+
+/// Conversion of a positional argument into a typed value
+///
+/// Used by the `at_set!` macro. The field is `None` when the argument is
+/// absent: plain types then fail with `AtError::MissingArg`, while
+/// `Option<T>` yields `None` so trailing parameters can be optional.
+///
+/// Integers follow the `Args::get_i32` grammar, booleans accept `0`/`1`
+/// and `&str` takes the raw field.
+pub trait FromAtArg<'a>: Sized {
+
+    /// Convert an argument, `None` meaning the argument is absent
+    fn from_at_arg(field: Option<&'a str>) -> Result<Self, AtError>;
+
+}
+
+macro_rules! impl_from_at_arg_int {
+    ($($ty:ty),*) => {
+        $(
+            impl<'a> FromAtArg<'a> for $ty {
+                fn from_at_arg(field: Option<&'a str>) -> Result<Self, AtError> {
+                    let value = parse_int(field.ok_or(AtError::MissingArg)?)?;
+                    <$ty>::try_from(value).map_err(|_| AtError::InvalidArgs)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_at_arg_int!(u8, u16, u32, i8, i16, i32);
+
+impl<'a> FromAtArg<'a> for bool {
+    fn from_at_arg(field: Option<&'a str>) -> Result<Self, AtError> {
+        match field.ok_or(AtError::MissingArg)? {
+            "0" => Ok(false),
+            "1" => Ok(true),
+            _ => Err(AtError::InvalidArgs),
+        }
+    }
+}
+
+impl<'a> FromAtArg<'a> for &'a str {
+    fn from_at_arg(field: Option<&'a str>) -> Result<Self, AtError> {
+        field.ok_or(AtError::MissingArg)
+    }
+}
+
+impl<'a, T> FromAtArg<'a> for Option<T>
+where
+    T: FromAtArg<'a> {
+    fn from_at_arg(field: Option<&'a str>) -> Result<Self, AtError> {
+        field.map(|f| T::from_at_arg(Some(f))).transpose()
+    }
+}
+
+/// Conversion of the whole argument list into a tuple (see
+/// `Args::parse_tuple`), implemented for tuples of 1 to 6 `FromAtArg` types
+pub trait FromAtArgs<'a>: Sized {
+
+    /// Convert every positional argument, in order
+    fn from_at_args(args: &Args<'a>) -> Result<Self, AtError>;
+
+}
+
+macro_rules! impl_from_at_args_tuple {
+    ($($len:literal => ($($ty:ident $index:tt),+)),* $(,)?) => {
+        $(
+            impl<'a, $($ty),+> FromAtArgs<'a> for ($($ty,)+)
+            where
+                $($ty: FromAtArg<'a>),+ {
+                fn from_at_args(args: &Args<'a>) -> Result<Self, AtError> {
+                    if args.len() != $len {
+                        return Err(AtError::InvalidArgs);
+                    }
+                    Ok(($($ty::from_at_arg(args.get($index))?,)+))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_at_args_tuple!(
+    1 => (A 0),
+    2 => (A 0, B 1),
+    3 => (A 0, B 1, C 2),
+    4 => (A 0, B 1, C 2, D 3),
+    5 => (A 0, B 1, C 2, D 3, E 4),
+    6 => (A 0, B 1, C 2, D 3, E 4, F 5),
+);
+
+/// Strip the AT prefix from a command name
+///
+/// Handles the `AT+` (extended), `AT&` (ampersand) and plain `AT` (basic,
+/// e.g. `ATE0`) prefixes, in that order, so `"AT+LED"` gives `"LED"`,
+/// `"AT&V"` gives `"V"` and `"ATE"` gives `"E"`. Names without a
+/// recognizable prefix are returned unchanged. Matching is case-sensitive.
+///
+/// The bare `AT` rule applies to any name starting with `AT`, so an
+/// unprefixed name such as `"ATTACH"` gives `"TACH"`; keep the `AT+` prefix
+/// on such names (`"AT+ATTACH"` gives `"ATTACH"`).
+pub fn command_suffix(name: &str) -> &str {
+    name.strip_prefix("AT+")
+        .or_else(|| name.strip_prefix("AT&"))
+        .or_else(|| name.strip_prefix("AT"))
+        .unwrap_or(name)
+}
+
+/// Percent-decode `input` into `out` (`%41` gives `A`, `+` gives a space,
+/// as in URL query strings)
+///
+/// # Returns
+/// * `Ok(&str)` - The decoded text, borrowing `out`
+/// * `Err(AtError::InvalidArgs)` - A `%` not followed by two hex digits, or
+///   decoded bytes that are not valid UTF-8
+/// * `Err(AtError::BufferFull)` - The decoded text does not fit in `out`
+pub fn percent_decode<'o>(input: &str, out: &'o mut [u8]) -> Result<&'o str, AtError> {
+    let mut bytes = input.bytes();
+    let mut len = 0;
+    while let Some(byte) = bytes.next() {
+        let decoded = match byte {
+            b'%' => {
+                let hi = bytes.next().and_then(|b| char::from(b).to_digit(16));
+                let lo = bytes.next().and_then(|b| char::from(b).to_digit(16));
+                match (hi, lo) {
+                    (Some(hi), Some(lo)) => (hi * 16 + lo) as u8,
+                    _ => return Err(AtError::InvalidArgs),
+                }
+            }
+            b'+' => b' ',
+            byte => byte,
+        };
+        *out.get_mut(len).ok_or(AtError::BufferFull)? = decoded;
+        len += 1;
+    }
+    core::str::from_utf8(&out[..len]).map_err(|_| AtError::InvalidArgs)
+}
+
+/// Parse a decimal integer with an optional sign (`[+-]?[0-9]+`)
+/// Overflow is reported as `AtError::InvalidArgs`, never as a panic
+pub(crate) fn parse_int(field: &str) -> Result<i64, AtError> {
+    let (negative, digits) = match field.as_bytes().first() {
+        Some(b'-') => (true, &field[1..]),
+        Some(b'+') => (false, &field[1..]),
+        _ => (false, field),
+    };
+    if digits.is_empty() {
+        return Err(AtError::InvalidArgs);
+    }
+
+    let mut value: i64 = 0;
+    for b in digits.bytes() {
+        if !b.is_ascii_digit() {
+            return Err(AtError::InvalidArgs);
+        }
+        value = value
+            .checked_mul(10)
+            .and_then(|v| v.checked_add(i64::from(b - b'0')))
+            .ok_or(AtError::InvalidArgs)?;
+    }
+
+    Ok(if negative { -value } else { value })
 }
 
 
@@ -91,4 +810,36 @@ macro_rules! at_modules {
             ]
         };
     };
-}
\ No newline at end of file
+}
+
+/// Macro to generate a typed `AtContext::set` handler
+///
+/// Each parameter is parsed from the positional argument at the same index
+/// through `FromAtArg`; a missing or malformed argument returns the
+/// corresponding `AtError` before the body runs. Use `Option<T>` for
+/// optional trailing parameters. Extra arguments are ignored (see
+/// `AtParser::set_strict_args` to reject them). `self` must be listed first
+/// so the body can use it:
+///
+/// ```ignore
+/// impl AtContext for LedModule {
+///     at_set!(|self, state: bool, brightness: Option<u8>| {
+///         self.state = state;
+///         self.brightness = brightness.unwrap_or(self.brightness);
+///         Ok("")
+///     });
+/// }
+/// ```
+#[macro_export]
+macro_rules! at_set {
+    (|$self:ident $(, $arg:ident : $ty:ty)* $(,)?| $body:block) => {
+        fn set(&mut $self, args: $crate::Args) -> $crate::AtResult<'static> {
+            let mut _index = 0usize;
+            $(
+                let $arg: $ty = $crate::FromAtArg::from_at_arg(args.get(_index))?;
+                _index += 1;
+            )*
+            $body
+        }
+    };
+}
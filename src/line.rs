@@ -0,0 +1,137 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+
+//! Incremental line assembly for byte-oriented transports (UART, USB CDC)
+//!
+//! Bytes are pushed one at a time as they arrive; a complete line is
+//! returned once a terminator (`\r` or `\n` by default, see
+//! `LineReader::set_terminators`) is received, ready to be passed
+//! to `AtParser::execute`. Empty lines, such as the `\n` of a CRLF pair, are
+//! swallowed.
+//!
+//! # Continuation
+//!
+//! A backslash immediately before the terminator joins the next line: the
+//! backslash and the line break are dropped and assembly goes on, so
+//! `AT+CFG=1,2,\` followed by `3,4` yields `AT+CFG=1,2,3,4`. The other
+//! half of a CRLF (or LFCR) pair right after a continuation is ignored, while
+//! a blank line completes the joined line as it stands.
+//!
+//! Only an odd number of trailing backslashes is a continuation. An escaped
+//! backslash (`\\`) at the end of the payload is a literal and the line is
+//! completed with it unchanged, so a payload really ending with a backslash
+//! must be sent with the backslash doubled.
+
+use crate::AtError;
+
+/// Line assembler with a fixed buffer of `N` bytes
+pub struct LineReader<const N: usize> {
+    /// Bytes of the line being assembled
+    buf: [u8; N],
+    /// Number of valid bytes in `buf`
+    len: usize,
+    /// The current line exceeded the buffer and is being discarded
+    overflow: bool,
+    /// Terminator of the continuation just consumed, whose CRLF partner is
+    /// skipped
+    continued: Option<u8>,
+    /// Characters completing a line
+    terminators: &'static [char],
+}
+
+impl<const N: usize> LineReader<N> {
+
+    /// Create an empty line reader
+    pub const fn new() -> Self {
+        Self {
+            buf: [0; N],
+            len: 0,
+            overflow: false,
+            continued: None,
+            terminators: &['\r', '\n'],
+        }
+    }
+
+    /// Set the characters completing a line (default `\r` and `\n`), e.g.
+    /// the set given to `AtParser::set_line_terminators`
+    /// Bytes are matched one by one, so only ASCII terminators are
+    /// recognized; other characters in the set never complete a line.
+    pub fn set_terminators(&mut self, terminators: &'static [char]) {
+        self.terminators = terminators;
+    }
+
+    /// Discard the line being assembled
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.overflow = false;
+        self.continued = None;
+    }
+
+    /// Feed one received byte
+    ///
+    /// # Returns
+    /// * `None` - The line is not complete yet (or was empty)
+    /// * `Some(Ok(line))` - A complete line, without its terminator
+    /// * `Some(Err(AtError::LineTooLong))` - The line did not fit in `N`
+    ///   bytes; it was discarded up to its terminator
+    /// * `Some(Err(AtError::InvalidEncoding))` - The line is not valid UTF-8
+    pub fn push(&mut self, byte: u8) -> Option<Result<&str, AtError>> {
+        if !(byte.is_ascii() && self.terminators.contains(&char::from(byte))) {
+            self.continued = None;
+            if self.len < N {
+                self.buf[self.len] = byte;
+                self.len += 1;
+            } else {
+                self.overflow = true;
+            }
+            return None;
+        }
+
+        if let Some(previous) = self.continued.take()
+            && previous != byte {
+            return None;
+        }
+        if self.overflow {
+            self.clear();
+            return Some(Err(AtError::LineTooLong));
+        }
+
+        let trailing = self.buf[..self.len].iter().rev().take_while(|&&b| b == b'\\').count();
+        if trailing % 2 == 1 {
+            self.len -= 1;
+            self.continued = Some(byte);
+            return None;
+        }
+        if self.len == 0 {
+            return None;
+        }
+
+        let len = self.len;
+        self.len = 0;
+        Some(core::str::from_utf8(&self.buf[..len]).map_err(|_| AtError::InvalidEncoding))
+    }
+}
+
+impl<const N: usize> Default for LineReader<N> {
+
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -17,8 +17,17 @@
  *
  ***************************************************************************/
  
-use crate::context::AtContext;
-use crate::{AtError, AtResult, Args};
+use core::ffi::{c_char, CStr};
+use core::fmt::Write;
+use core::mem::MaybeUninit;
+
+use crate::context::{AtContext, AtContextWith, AtFallback};
+#[cfg(feature = "async")]
+use crate::context::AsyncAtContext;
+#[cfg(feature = "history")]
+use crate::history::{History, HistoryEntry};
+use crate::response::{BoolStyle, ResponseWriter};
+use crate::{command_suffix, AtError, AtResult, Args, ConfigError, Response, SUPPRESSED};
 
 /*
 AT Command Forms:
@@ -29,41 +38,1138 @@ AT Command Forms:
  */
 
 /// Represents the different forms an AT command can take
-enum AtForm<'a> {
+pub enum AtForm<'a> {
     /// Execute command without parameters (AT+CMD)
     Exec,
     /// Query the current state (AT+CMD?)
+    #[cfg(feature = "query")]
     Query,
     /// Test command availability or get valid ranges (AT+CMD=?)
+    #[cfg(feature = "test")]
     Test,
+    /// Test command with a parameter (AT+CMD=<args>?)
+    #[cfg(feature = "test")]
+    TestWith(Args<'a>),
     /// Set command with arguments (AT+CMD=args)
     Set(Args<'a>),
 }
 
+impl<'a> AtForm<'a> {
+    /// Get the argument-less kind of this form
+    pub fn kind(&self) -> FormKind {
+        match self {
+            AtForm::Exec => FormKind::Exec,
+            #[cfg(feature = "query")]
+            AtForm::Query => FormKind::Query,
+            #[cfg(feature = "test")]
+            AtForm::Test | AtForm::TestWith(_) => FormKind::Test,
+            AtForm::Set(_) => FormKind::Set,
+        }
+    }
+}
+
+/// Kind of an AT command form, without its arguments
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormKind {
+    /// Execute command (AT+CMD)
+    Exec,
+    /// Query command (AT+CMD?)
+    #[cfg(feature = "query")]
+    Query,
+    /// Test command (AT+CMD=?)
+    #[cfg(feature = "test")]
+    Test,
+    /// Set command (AT+CMD=args)
+    Set,
+}
+
+/// Detailed parse failure returned by the standalone `parse` function
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseError {
+    /// Form the input looked like (e.g. Set for "=1")
+    pub form: FormKind,
+    /// Why the input was rejected
+    pub reason: ParseErrorReason,
+    /// Byte offset where parsing failed, relative to the trimmed input
+    pub offset: usize,
+}
+
+/// Reason of a `ParseError`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseErrorReason {
+    /// The command name before the form marker is empty, or is only an
+    /// `AT`/`AT+`/`AT&` prefix (e.g. "=1", "?", "AT+=1", "AT=1")
+    EmptyName,
+    /// The Set marker is doubled (e.g. "AT+CMD==1")
+    DoubledEquals,
+}
+
+impl core::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.reason {
+            ParseErrorReason::EmptyName => write!(f, "{:?} form with an empty command name at byte {}", self.form, self.offset),
+            ParseErrorReason::DoubledEquals => write!(f, "doubled '=' at byte {}", self.offset),
+        }
+    }
+}
+
+impl From<ParseError> for AtError {
+    fn from(e: ParseError) -> Self {
+        AtError::SyntaxAt(e.offset)
+    }
+}
+
 /// The main AT command parser
-/// Generic over T which must implement AtContext trait
-pub struct AtParser<'a, T>
-where
-    T: AtContext {
-    /// Array of registered commands with their name and handler
-    pub commands: &'a mut [(&'static str, &'a mut T)],
+/// Generic over T which implements AtContext (or AsyncAtContext with the
+/// `async` feature)
+pub struct AtParser<'a, T> {
+    /// Array of registered commands with their name and handler, replaced
+    /// through `set_commands` only so `max_name_len` stays in sync
+    commands: &'a mut [(&'a str, &'a mut T)],
+    /// Vendor extension table registered with `set_secondary_commands`
+    secondary: &'a mut [(&'a str, &'a mut T)],
+    /// Additional command tables registered with `add_group`
+    groups: [Option<&'a mut [(&'a str, &'a mut T)]>; MAX_GROUPS],
+    /// Renamed commands as (old name, new name), see `add_remap`
+    remaps: [Option<(&'a str, &'a str)>; MAX_REMAPS],
+    /// Maximum accepted input length in bytes for `execute_bytes`
+    max_line_len: usize,
+    /// Ignore empty/whitespace-only lines instead of reporting an error
+    ignore_empty: bool,
+    /// Post-processing hook applied to every successful response
+    response_filter: Option<ResponseFilter>,
+    /// Handler for inputs that match no registered command
+    fallback: Option<&'a mut (dyn AtFallback + Send)>,
+    /// Handler for the bare `AT` ping
+    ping: Option<&'a mut (dyn AtContext + Send)>,
+    /// Maximum number of comma-separated arguments accepted by the Set form
+    max_args: usize,
+    /// Reject Set arguments beyond the arity declared by the module
+    strict_args: bool,
+    /// Prepend `+NAME: ` to exec/query payloads on the framed path
+    response_prefix: bool,
+    /// Renders the error line of the framed paths
+    error_formatter: Option<ErrorFormatter>,
+    /// Success terminator word of the framed paths
+    ok_keyword: &'static str,
+    /// Error terminator word of the framed paths
+    error_keyword: &'static str,
+    /// Omit the result codes of the framed paths
+    quiet: bool,
+    /// Text of boolean values in responses
+    bool_style: BoolStyle,
+    /// Input clean-up hook applied before parsing
+    normalizer: Option<InputNormalizer>,
+    /// Reject any non-ASCII input byte
+    ascii_only: bool,
+    /// Accept `AT+CMD <args>` as a Set
+    space_args: bool,
+    /// Character separating the name from the Set arguments
+    set_delimiter: char,
+    /// Current device state, checked against `AtContext::state_mask`
+    state: u8,
+    /// Long-running operation in progress, rejecting every command
+    busy: bool,
+    /// Entry receiving the bytes of the open data phase
+    data_target: Option<&'a str>,
+    /// Sequence leaving data mode
+    escape: Option<&'static [u8]>,
+    /// Characters ending an input line, stripped from its end
+    line_terminators: &'static [char],
+    /// Length of the escape sequence prefix received so far
+    escape_matched: usize,
+    /// Callback reporting every executed command
+    observer: Option<Observer>,
+    /// Parser-level answers for forms a module does not support
+    form_defaults: FormDefaults,
+    /// Longest name any registered entry can match, kept by the table
+    /// setters, `add_group` and `clear_commands` (`usize::MAX` with indexed
+    /// entries)
+    max_name_len: usize,
+    /// Log of the most recent commands
+    #[cfg(feature = "history")]
+    history: Option<&'a mut (dyn History<'a> + Send)>,
 }
 
-impl<'a, T> AtParser<'a, T>
-where
-    T: AtContext {
+/// Compile-time check: a parser over `Send` modules is `Send`, so it can be
+/// moved to another task or wrapped in `sync::SyncAtParser`
+const _: () = {
+    const fn assert_send<S: Send>() {}
+    #[allow(dead_code)]
+    const fn parser_is_send<'a, T: Send + 'a>() {
+        assert_send::<AtParser<'a, T>>();
+    }
+};
+
+/// Entry of a command table declared in C (see
+/// `AtParser::from_sentinel_table`), laid out as
+/// `struct { const char *name; T *handler; }`
+#[repr(C)]
+#[derive(Debug)]
+pub struct SentinelEntry<T> {
+    /// NUL-terminated UTF-8 command name; null or empty for the sentinel
+    pub name: *const c_char,
+    /// Module handling the command; ignored for the sentinel
+    pub handler: *mut T,
+}
+
+// SAFETY: an entry is plain addresses, only dereferenced by
+// `from_sentinel_table` whose caller vouches for them; this lets C-shaped
+// tables live in a `static`
+unsafe impl<T> Sync for SentinelEntry<T> {}
+
+/// Default maximum number of arguments accepted by the Set form
+pub const DEFAULT_MAX_ARGS: usize = 32;
+
+/// Response post-processing hook (see `AtParser::set_response_filter`)
+///
+/// Responses are `&'static str`, so the filter can only return another
+/// static slice: a sub-slice of its input (e.g. stripping a prefix) or a
+/// different static string (e.g. redaction). To build new text such as an
+/// added prefix, write through the framed/buffer path instead.
+pub type ResponseFilter = fn(&'static str) -> &'static str;
+
+/// Parser-level answers for unsupported forms (see
+/// `AtParser::set_form_defaults`); everything is disabled by default
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FormDefaults {
+    /// Answer an unsupported query (`AT+CMD?`) with the exec response
+    pub query_from_exec: bool,
+    /// Answer an unsupported test (`AT+CMD=?`) with `AtContext::help`
+    /// (an empty help text yields a bare `OK`)
+    pub test_from_help: bool,
+}
+
+/// Snapshot of the parser mode settings (see `AtParser::settings`), e.g.
+/// for `AT&W`-style save/restore of the I/O configuration
+///
+/// Only plain settings are included. Commands, groups, the fallback, the
+/// ping handler and the history, as well as the hooks (response filter,
+/// error formatter, normalizer, observer), are not part of the snapshot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParserSettings {
+    /// See `AtParser::set_max_line_len`
+    pub max_line_len: usize,
+    /// See `AtParser::set_ignore_empty`
+    pub ignore_empty: bool,
+    /// See `AtParser::set_max_args`
+    pub max_args: usize,
+    /// See `AtParser::set_strict_args`
+    pub strict_args: bool,
+    /// See `AtParser::set_response_prefix`
+    pub response_prefix: bool,
+    /// See `AtParser::set_ok_keyword`
+    pub ok_keyword: &'static str,
+    /// See `AtParser::set_error_keyword`
+    pub error_keyword: &'static str,
+    /// See `AtParser::set_quiet`
+    pub quiet: bool,
+    /// See `AtParser::set_bool_style`
+    pub bool_style: BoolStyle,
+    /// See `AtParser::set_ascii_only`
+    pub ascii_only: bool,
+    /// See `AtParser::set_space_args`
+    pub space_args: bool,
+    /// See `AtParser::set_set_delimiter`
+    pub set_delimiter: char,
+    /// See `AtParser::set_escape_sequence`
+    pub escape: Option<&'static [u8]>,
+    /// See `AtParser::set_line_terminators`
+    pub line_terminators: &'static [char],
+    /// See `AtParser::set_form_defaults`
+    pub form_defaults: FormDefaults,
+}
+
+/// Command execution callback (see `AtParser::set_observer`): input,
+/// matched command name and result
+pub type Observer = fn(&str, Option<&str>, &AtResult<'static>);
+
+/// Input clean-up hook (see `AtParser::set_normalizer`)
+///
+/// The returned slice must borrow from the input (`for<'s> fn(&'s str) ->
+/// &'s str`): the normalizer can only narrow the line, e.g. skip leading
+/// noise bytes or cut at the first non-printable character, never build a
+/// new string.
+pub type InputNormalizer = fn(&str) -> &str;
+
+/// Error line renderer of the framed paths (see
+/// `AtParser::set_error_formatter`)
+///
+/// Writes the whole error line, terminator included, e.g.
+/// `+CME ERROR: 3\r\n` from `AtError::code`.
+pub type ErrorFormatter = fn(AtError, &mut dyn Write) -> core::fmt::Result;
+
+/// Maximum number of command groups that can be added with `add_group`
+pub const MAX_GROUPS: usize = 4;
+
+/// Maximum number of command renames that can be added with `add_remap`
+pub const MAX_REMAPS: usize = 8;
+
+/// Default command name length limit of `AtParser::set_commands_checked`
+pub const MAX_NAME_LEN: usize = 32;
+
+/// Default maximum input length in bytes accepted by `execute_bytes`
+pub const DEFAULT_MAX_LINE_LEN: usize = 256;
+
+impl<'a, T> AtParser<'a, T> {
 
     /// Create a new empty parser
     pub fn new() -> Self {
-        Self { commands: & mut [] }
+        Self {
+            commands: & mut [],
+            secondary: &mut [],
+            groups: [const { None }; MAX_GROUPS],
+            remaps: [None; MAX_REMAPS],
+            max_line_len: DEFAULT_MAX_LINE_LEN,
+            ignore_empty: true,
+            response_filter: None,
+            fallback: None,
+            ping: None,
+            max_args: DEFAULT_MAX_ARGS,
+            strict_args: false,
+            response_prefix: false,
+            error_formatter: None,
+            ok_keyword: "OK",
+            error_keyword: "ERROR",
+            quiet: false,
+            bool_style: BoolStyle::ZeroOne,
+            normalizer: None,
+            ascii_only: false,
+            space_args: false,
+            set_delimiter: '=',
+            state: 0,
+            busy: false,
+            data_target: None,
+            escape: Some(b"+++"),
+            escape_matched: 0,
+            line_terminators: &['\r', '\n'],
+            observer: None,
+            form_defaults: FormDefaults { query_from_exec: false, test_from_help: false },
+            max_name_len: 0,
+            #[cfg(feature = "history")]
+            history: None,
+        }
+    }
+
+    /// Create a parser over a command table declared in C, terminated by a
+    /// sentinel entry with a null or empty name (null-terminated array
+    /// idiom) without a separate count
+    ///
+    /// The entries before the sentinel are copied into `storage`, which
+    /// becomes the main table exactly as with `set_commands`; the sentinel
+    /// itself is never registered and its handler is not read.
+    ///
+    /// # Returns
+    /// * `Ok(parser)` - The parser over the copied entries
+    /// * `Err(ConfigError::InvalidName)` - A name is not valid UTF-8
+    /// * `Err(ConfigError::NoHandler(name))` - An entry has a null handler
+    /// * `Err(ConfigError::TableFull)` - `storage` has fewer slots than
+    ///   the table has entries
+    ///
+    /// # Safety
+    /// * `table` must be non-null, aligned and point to an array of
+    ///   initialized entries ending with the sentinel.
+    /// * Every name up to the sentinel must point to a NUL-terminated string
+    ///   that is not modified for `'a`.
+    /// * Every handler up to the sentinel must point to a valid `T` that is
+    ///   not read or written other than through the parser for `'a`, and no
+    ///   two entries may share a handler.
+    pub unsafe fn from_sentinel_table(
+        table: *const SentinelEntry<T>,
+        storage: &'a mut [MaybeUninit<(&'a str, &'a mut T)>],
+    ) -> Result<Self, ConfigError<'a>> {
+        let mut len = 0;
+        loop {
+            // SAFETY: the caller guarantees the array is initialized up to
+            // and including the sentinel, which stops the scan
+            let entry = unsafe { &*table.add(len) };
+            if entry.name.is_null() {
+                break;
+            }
+            // SAFETY: non-null names are NUL-terminated and left untouched
+            // for `'a`, as guaranteed by the caller
+            let name: &'a CStr = unsafe { CStr::from_ptr(entry.name) };
+            if name.is_empty() {
+                break;
+            }
+            let name = name.to_str().map_err(|_| ConfigError::InvalidName)?;
+            if entry.handler.is_null() {
+                return Err(ConfigError::NoHandler(name));
+            }
+            let slot = storage.get_mut(len).ok_or(ConfigError::TableFull)?;
+            // SAFETY: the handler is valid and exclusively borrowed for
+            // `'a`, as guaranteed by the caller
+            slot.write((name, unsafe { &mut *entry.handler }));
+            len += 1;
+        }
+        // SAFETY: the first `len` slots were initialized above
+        let commands = unsafe { core::slice::from_raw_parts_mut(storage.as_mut_ptr().cast(), len) };
+        let mut parser = Self::new();
+        parser.set_commands(commands);
+        Ok(parser)
     }
 
     /// Register commands that this parser will handle
-    pub fn set_commands(&mut self, commands: &'a mut [(&'static str, &'a mut T)]) {
+    /// This fully replaces the previously registered main table; the
+    /// secondary table and the groups added with `add_group` are kept.
+    ///
+    /// Names only need to outlive the parser (`'a`), so they can be string
+    /// literals or be built at boot in a runtime buffer (e.g. from a serial
+    /// number). A runtime buffer stays borrowed, and thus frozen, for as long
+    /// as the parser lives, and the names returned by `command_names`,
+    /// `validate` or the history carry `'a` instead of `'static`.
+    ///
+    /// A name ending with `#` (e.g. `"AT+SOCK#"`) registers an indexed
+    /// command: it matches the stem followed by one or more ASCII digits
+    /// (`AT+SOCK0`, `AT+SOCK17`, up to `u32::MAX`) and dispatches to the
+    /// `AtContext::*_indexed` handlers with the parsed index. `AT+SOCK`
+    /// alone, signs and overflowing indices do not match. Entries are tried
+    /// in registration order, so an exact name registered before the indexed
+    /// entry overrides a single instance. Indexed commands are dispatched
+    /// context-free by `execute_with` and are not supported by
+    /// `execute_async`.
+    ///
+    /// The length of the longest registered name is computed here (and
+    /// updated by `set_secondary_commands` and `add_group`), so lookups
+    /// reject longer input names without scanning the tables. Indexed
+    /// entries accept any number of leading zeros and disable this
+    /// shortcut.
+    pub fn set_commands(&mut self, commands: &'a mut [(&'a str, &'a mut T)]) {
         self.commands = commands;
+        self.max_name_len = self.entries().map(|(n, _)| name_len_bound(n)).max().unwrap_or(0);
+    }
+
+    /// The main command table registered with `set_commands`
+    /// Handlers are reached mutably through `command_mut`; names can only be
+    /// changed by registering a new table.
+    pub fn commands(&self) -> &[(&'a str, &'a mut T)] {
+        self.commands
+    }
+
+    /// Register the main command table like `set_commands`, after checking
+    /// that every name is at most `MAX` bytes long
+    ///
+    /// Catches buffer-sizing bugs when names are copied into fixed buffers
+    /// elsewhere in the firmware. Use `MAX_NAME_LEN` (32) unless the
+    /// firmware has its own limit: `set_commands_checked::<MAX_NAME_LEN>`.
+    /// Names are checked as registered, so an indexed entry counts its `#`
+    /// while its instances (`AT+SOCK12`) may be longer.
+    ///
+    /// # Returns
+    /// * `Ok(())` - The table was registered
+    /// * `Err(ConfigError::NameTooLong(name))` - The first name longer than
+    ///   `MAX`; the previous table is kept
+    pub fn set_commands_checked<const MAX: usize>(&mut self, commands: &'a mut [(&'a str, &'a mut T)]) -> Result<(), ConfigError<'a>> {
+        if let Some((name, _)) = commands.iter().find(|(name, _)| name.len() > MAX) {
+            return Err(ConfigError::NameTooLong(name));
+        }
+        self.set_commands(commands);
+        Ok(())
+    }
+
+    /// Register the secondary command table, e.g. vendor extensions loaded
+    /// conditionally on top of a stable core set
+    /// This fully replaces the previously registered secondary table; the
+    /// main table and the groups are kept.
+    ///
+    /// Lookup precedence: the main table (`set_commands`) first, then this
+    /// table, then the groups (`add_group`). The first matching name wins,
+    /// so a core command always shadows a vendor command of the same name.
+    pub fn set_secondary_commands(&mut self, commands: &'a mut [(&'a str, &'a mut T)]) {
+        self.secondary = commands;
+        self.max_name_len = self.entries().map(|(n, _)| name_len_bound(n)).max().unwrap_or(0);
+    }
+
+    /// Unregister every command: main, secondary and groups tables alike
+    /// Afterwards every input is reported as `AtError::UnknownCommand`
+    /// (or handed to the fallback, if any) until commands are registered again.
+    pub fn clear_commands(&mut self) {
+        self.commands = &mut [];
+        self.secondary = &mut [];
+        self.groups = [const { None }; MAX_GROUPS];
+        self.max_name_len = 0;
+    }
+
+    /// Register an additional command group (e.g. all `AT+WIFI...` commands)
+    ///
+    /// Lookup searches the table given to `set_commands` first, then the
+    /// secondary table, then each group in registration order; the first
+    /// matching name wins.
+    ///
+    /// # Returns
+    /// * `Ok(())` - The group was added
+    /// * `Err(AtError::BufferFull)` - `MAX_GROUPS` groups are already registered
+    pub fn add_group(&mut self, group: &'a mut [(&'a str, &'a mut T)]) -> Result<(), AtError> {
+        let slot = self.groups
+            .iter_mut()
+            .find(|g| g.is_none())
+            .ok_or(AtError::BufferFull)?;
+        let group_max = group.iter().map(|(n, _)| name_len_bound(n)).max().unwrap_or(0);
+        *slot = Some(group);
+        self.max_name_len = self.max_name_len.max(group_max);
+        Ok(())
+    }
+
+    /// Route a renamed command to its new name, e.g. `("AT+OLD", "AT+NEW")`
+    /// during the deprecation window of a firmware update
+    ///
+    /// The rename is applied to the command name of every input line before
+    /// lookup, so it takes precedence over direct matches: an entry still
+    /// registered under `old` is never reached. Renames are exact and
+    /// case-sensitive, apply once (no chains) and do not rewrite indexed
+    /// instances. The handler, the history and the observer see the new
+    /// name, the fallback sees the name as sent. `validate` and
+    /// `command_mut` follow renames too. Registering `old` again replaces its
+    /// target.
+    ///
+    /// # Returns
+    /// * `Ok(())` - The rename was added
+    /// * `Err(AtError::BufferFull)` - `MAX_REMAPS` renames are already registered
+    pub fn add_remap(&mut self, old: &'a str, new: &'a str) -> Result<(), AtError> {
+        let slot = match self.remaps.iter().position(|r| r.is_some_and(|(o, _)| o == old)) {
+            Some(index) => &mut self.remaps[index],
+            None => self.remaps
+                .iter_mut()
+                .find(|r| r.is_none())
+                .ok_or(AtError::BufferFull)?,
+        };
+        *slot = Some((old, new));
+        Ok(())
+    }
+
+    /// Set the maximum input length in bytes accepted by `execute_bytes`
+    /// Defaults to `DEFAULT_MAX_LINE_LEN`
+    pub fn set_max_line_len(&mut self, max_line_len: usize) {
+        self.max_line_len = max_line_len;
+    }
+
+    /// Choose how empty or whitespace-only lines (e.g. a stray "\r\n") are handled
+    ///
+    /// * `true` (default) - the line is ignored: `execute` returns `Ok("")` and
+    ///   the framed paths write nothing
+    /// * `false` - the line is reported as `AtError::UnknownCommand`
+    pub fn set_ignore_empty(&mut self, ignore_empty: bool) {
+        self.ignore_empty = ignore_empty;
+    }
+
+    /// Install a hook applied to every successful response (including empty
+    /// payloads and query rows) before it is returned or framed
+    /// Errors are never passed to the filter; `None` removes the hook.
+    pub fn set_response_filter(&mut self, filter: Option<ResponseFilter>) {
+        self.response_filter = filter;
+    }
+
+    /// Install a hook cleaning every input line before it is parsed
+    ///
+    /// The normalizer receives the raw line (or each `;` segment for
+    /// `execute_line`) and returns a sub-slice of it; surrounding whitespace
+    /// is trimmed afterwards. The result is what the parser, the fallback and
+    /// `AtError::SyntaxAt` offsets see. `None` (default) removes the hook.
+    pub fn set_normalizer(&mut self, normalizer: Option<InputNormalizer>) {
+        self.normalizer = normalizer;
+    }
+
+    /// Configure the answers of forms a module does not implement
+    ///
+    /// The module always comes first: a parser default is only used when
+    /// the module's handler returns `AtError::NotSupported`, and when the
+    /// default itself is disabled or fails with `NotSupported` that error is
+    /// reported unchanged. Defaults do not apply to indexed entries, nor to
+    /// `query_all_into`.
+    pub fn set_form_defaults(&mut self, defaults: FormDefaults) {
+        self.form_defaults = defaults;
+    }
+
+    /// Accept arguments separated from the name by a space (disabled by
+    /// default), for dialects that do not use `=`
+    ///
+    /// When enabled, an Exec line containing a space is split at the first
+    /// one: `AT+DIAL 555` is dispatched as the Set `AT+DIAL=555`. The
+    /// arguments keep their raw text, so `AT+CMD 1 2` gives the single field
+    /// `1 2`; split it with `Args::raw_payload().split_ascii_whitespace()`.
+    /// Lines using `?` or `=` are parsed as usual. Exec lines of command
+    /// names with inner spaces are split too, so avoid such names here.
+    pub fn set_space_args(&mut self, space_args: bool) {
+        self.space_args = space_args;
+    }
+
+    /// Set the character separating the name from the Set arguments
+    /// (default `=`), for dialects writing `AT+CMD#value`
+    ///
+    /// The Set form changes and the Test forms follow (`AT+CMD#?`,
+    /// `AT+CMD#1?`), while `AT+CMD=?` stays a Test too. A doubled
+    /// delimiter (`AT+CMD##1`) is a syntax error like `==`. The delimiter
+    /// cannot appear in command names, so with `#` indexed entries
+    /// (`AT+SOCK#`) only match through their numeric instances
+    /// (`AT+SOCK1#5`). The standalone `parse` function always uses `=`.
+    pub fn set_set_delimiter(&mut self, delimiter: char) {
+        self.set_delimiter = delimiter;
+    }
+
+    /// Set the current device state (0 by default), e.g. disconnected or
+    /// connected for a modem
+    ///
+    /// Masking scheme: states are numbered 0 to 31 and each command declares
+    /// the states it accepts with `AtContext::state_mask`, bit `n` enabling
+    /// state `n` (`1 << CONNECTED`). A command invoked outside its states
+    /// fails with `AtError::NotSupported` before its handler runs (and is
+    /// handed to the history and observer without a name). The default mask
+    /// `u32::MAX` accepts every state, states from 32 up included, so
+    /// stateless modules need no change. Every path checks states,
+    /// `validate` and `execute_async` included.
+    pub fn set_state(&mut self, state: u8) {
+        self.state = state;
+    }
+
+    /// Mark a long-running operation as in progress (`true`) or finished
+    /// (`false`, the default)
+    ///
+    /// While busy, every command fails with `AtError::Busy` before lookup,
+    /// so no handler runs (the history and observer see it without a name);
+    /// empty lines are still ignored by the empty-line policy and the bytes
+    /// of an open data phase still reach `feed_data`; `validate` reports
+    /// it too. This prevents reentrancy on single-resource devices.
+    ///
+    /// The parser never sets the flag itself: every execute path borrows it
+    /// mutably, so a command cannot be re-entered while its handler runs.
+    /// The flag covers operations outliving their command, e.g. a handler
+    /// starting a flash erase or a transfer on another task (or a cancelled
+    /// `execute_async` whose resource is not released yet): set it once the
+    /// command has started the operation and clear it from the completion
+    /// event.
+    pub fn set_busy(&mut self, busy: bool) {
+        self.busy = busy;
+    }
+
+    /// Check whether a long-running operation is in progress (see
+    /// `set_busy`)
+    pub fn is_busy(&self) -> bool {
+        self.busy
+    }
+
+    /// Set the characters ending an input line (default `\r` and `\n`),
+    /// for transports terminating lines with e.g. a single `;` or a custom
+    /// byte
+    ///
+    /// Every execute path strips any run of terminators (and whitespace)
+    /// from the end of the line, after the normalizer, so `AT+A=1;` with
+    /// `;` configured is `AT+A=1`. Whitespace is always trimmed, whether
+    /// listed or not. Give the same set to `LineReader::set_terminators`
+    /// for incremental assembly.
+    ///
+    /// With `;` both a terminator and the `execute_line` batch separator,
+    /// a trailing `;` ends the line while inner ones still separate
+    /// commands; but a `LineReader` configured with `;` splits the stream
+    /// at every `;`, so each command of a batch arrives as its own line and
+    /// runs on its own, with its own final result code. Choose a terminator
+    /// other than `;` when batches must be answered as a whole.
+    pub fn set_line_terminators(&mut self, terminators: &'static [char]) {
+        self.line_terminators = terminators;
+    }
+
+    /// Set the sequence leaving data mode (default `+++`), `None` to
+    /// disable escapes (see `feed_data`)
+    ///
+    /// Guard time: modems only honor an escape surrounded by a pause in the
+    /// data flow. The parser has no clock, so the pause is modelled by the
+    /// chunks given to `feed_data`: the escape is recognized only when it
+    /// arrives alone, in one chunk or split across consecutive chunks
+    /// holding nothing else (`b"+++"`, or `b"+"` three times). Feed a chunk
+    /// after each silence detected by the UART (e.g. idle-line interrupt)
+    /// to get the usual guard-time behavior; an escape embedded in a
+    /// larger chunk (`b"a+++"`) is plain data. A partial escape is held
+    /// back and handed to the command as data once a chunk breaks it. The
+    /// command is not notified of the escape. An empty sequence disables
+    /// escapes like `None`.
+    pub fn set_escape_sequence(&mut self, escape: Option<&'static [u8]>) {
+        self.escape = escape.filter(|escape| !escape.is_empty());
+        self.escape_matched = 0;
+    }
+
+    /// Get the current device state (see `set_state`)
+    pub fn state(&self) -> u8 {
+        self.state
+    }
+
+    /// Install a callback invoked once per executed command, for logging or
+    /// metrics; `None` removes it
+    ///
+    /// It fires after dispatch, once the response filter has run and the
+    /// history was updated, and just before the result is returned (or,
+    /// on the framed paths, before the terminator is written). It receives
+    /// the normalized input, the registered name (`None` for unknown or
+    /// malformed commands) and the result; on the framed paths a multi-row
+    /// query reports `Ok("")`, and so does a suppressed response
+    /// (`SUPPRESSED`). Lines skipped by the empty-line policy are not
+    /// reported, and the result is never altered.
+    pub fn set_observer(&mut self, observer: Option<Observer>) {
+        self.observer = observer;
+    }
+
+    /// Accept pure ASCII input only (disabled by default)
+    ///
+    /// When enabled, any byte with the high bit set is treated as a framing
+    /// error: the line is rejected with `AtError::InvalidEncoding` before it
+    /// is parsed, even when it is valid UTF-8. The check runs after the
+    /// input normalizer, and `validate` applies it too.
+    pub fn set_ascii_only(&mut self, ascii_only: bool) {
+        self.ascii_only = ascii_only;
+    }
+
+    /// Set the success terminator word of `execute_framed` and
+    /// `execute_line` (default `OK`), e.g. `DONE` for proprietary hosts
+    pub fn set_ok_keyword(&mut self, keyword: &'static str) {
+        self.ok_keyword = keyword;
+    }
+
+    /// Set the error terminator word of `execute_framed` and
+    /// `execute_line` (default `ERROR`)
+    /// Ignored while an error formatter is installed (e.g. numeric
+    /// `+CME ERROR: <code>` results), which renders the whole error line.
+    pub fn set_error_keyword(&mut self, keyword: &'static str) {
+        self.error_keyword = keyword;
+    }
+
+    /// Install the renderer of the error line written by `execute_framed`
+    /// and `execute_line` when a command fails
+    /// `None` (default) restores the verbose `ERROR\r\n` line (see
+    /// `set_error_keyword`).
+    pub fn set_error_formatter(&mut self, formatter: Option<ErrorFormatter>) {
+        self.error_formatter = formatter;
+    }
+
+    /// Suppress the result codes of the framed paths, like `ATQ1` on modems
+    /// (disabled by default)
+    ///
+    /// When enabled, `execute_framed`, `execute_line` and `execute_into`
+    /// still write the payload lines but neither the success terminator nor
+    /// the error line, so a command without payload writes nothing at all.
+    /// The keywords and the error formatter are kept and apply again once
+    /// quiet mode is disabled. The plain `execute` result, the history and
+    /// the observer are not affected.
+    pub fn set_quiet(&mut self, quiet: bool) {
+        self.quiet = quiet;
+    }
+
+    /// Select how booleans are rendered in responses (`BoolStyle::ZeroOne`
+    /// by default)
+    ///
+    /// Modules read it back with `bool_style` when they are set up, and
+    /// write booleans with `BoolStyle::text` or `ResponseWriter::write_bool`,
+    /// so every command renders them the same way.
+    pub fn set_bool_style(&mut self, style: BoolStyle) {
+        self.bool_style = style;
+    }
+
+    /// Get the boolean style selected with `set_bool_style`
+    pub fn bool_style(&self) -> BoolStyle {
+        self.bool_style
+    }
+
+    /// Take a snapshot of the mode settings (see `ParserSettings` for what
+    /// is included), to restore later with `apply_settings`
+    pub fn settings(&self) -> ParserSettings {
+        ParserSettings {
+            max_line_len: self.max_line_len,
+            ignore_empty: self.ignore_empty,
+            max_args: self.max_args,
+            strict_args: self.strict_args,
+            response_prefix: self.response_prefix,
+            ok_keyword: self.ok_keyword,
+            error_keyword: self.error_keyword,
+            quiet: self.quiet,
+            bool_style: self.bool_style,
+            ascii_only: self.ascii_only,
+            space_args: self.space_args,
+            set_delimiter: self.set_delimiter,
+            escape: self.escape,
+            line_terminators: self.line_terminators,
+            form_defaults: self.form_defaults,
+        }
+    }
+
+    /// Restore mode settings taken with `settings`, as if every matching
+    /// setter was called; everything outside `ParserSettings` is kept
+    pub fn apply_settings(&mut self, settings: ParserSettings) {
+        self.max_line_len = settings.max_line_len;
+        self.ignore_empty = settings.ignore_empty;
+        self.max_args = settings.max_args;
+        self.strict_args = settings.strict_args;
+        self.response_prefix = settings.response_prefix;
+        self.ok_keyword = settings.ok_keyword;
+        self.error_keyword = settings.error_keyword;
+        self.quiet = settings.quiet;
+        self.bool_style = settings.bool_style;
+        self.ascii_only = settings.ascii_only;
+        self.space_args = settings.space_args;
+        self.set_escape_sequence(settings.escape);
+        self.line_terminators = settings.line_terminators;
+        self.set_delimiter = settings.set_delimiter;
+        self.form_defaults = settings.form_defaults;
+    }
+
+    /// Set the maximum number of comma-separated arguments accepted by the
+    /// Set form (defaults to `DEFAULT_MAX_ARGS`)
+    ///
+    /// Inputs with more arguments are rejected with `AtError::InvalidArgs`
+    /// before any handler runs; counting stops as soon as the limit is
+    /// exceeded, so hostile inputs with thousands of commas cost no more
+    /// than a single scan. Every comma counts, quoted ones and empty fields
+    /// included. The limit is not carried by `Args`: handlers still check
+    /// the fields they expect. `usize::MAX` lifts the limit.
+    pub fn set_max_args(&mut self, max_args: usize) {
+        self.max_args = max_args;
+    }
+
+    /// Enable strict argument checking (disabled by default)
+    ///
+    /// When enabled, a Set with more arguments than the module's
+    /// `AtContext::arity` is rejected with `AtError::InvalidArgs` before the
+    /// handler runs (e.g. `AT+LED=1,50,99` for a two-argument command),
+    /// and `validate` reports the same error. Modules that declare no arity
+    /// are never checked.
+    pub fn set_strict_args(&mut self, strict_args: bool) {
+        self.strict_args = strict_args;
+    }
+
+    /// Prefix exec/query payloads with the command name (disabled by default)
+    ///
+    /// When enabled, the framed paths (`execute_framed`, `execute_line`)
+    /// write every non-empty exec/query payload line of a registered command
+    /// as `+NAME: <response>`, where `NAME` is the command name without its
+    /// `AT+`/`AT&`/`AT` prefix (e.g. `AT+LED?` answering `1` becomes
+    /// `+LED: 1`). Test and Set responses, fallback responses and the plain
+    /// `execute` path are left untouched, so modules must not add the
+    /// prefix themselves when this is enabled.
+    pub fn set_response_prefix(&mut self, response_prefix: bool) {
+        self.response_prefix = response_prefix;
+    }
+
+    /// Attach a command log recording every executed command and its result
+    /// code, e.g. a `CommandHistory<N>` (`history` feature); `None` detaches it
+    #[cfg(feature = "history")]
+    pub fn set_history(&mut self, history: Option<&'a mut (dyn History<'a> + Send)>) {
+        self.history = history;
+    }
+
+    /// Iterate over the recorded commands, oldest first
+    /// Empty when no log is attached
+    #[cfg(feature = "history")]
+    pub fn recent(&self) -> impl Iterator<Item = HistoryEntry<'a>> {
+        let history = self.history.as_deref();
+        let len = history.map_or(0, |h| h.len());
+        (0..len).filter_map(move |i| history.and_then(|h| h.get(i)))
+    }
+
+    /// Install a catch-all handler invoked when no registered command matches
+    ///
+    /// The fallback receives the original (trimmed) input together with the
+    /// command name exactly as sent and the parsed form, so it can e.g.
+    /// forward the line verbatim to a peer chip. Its result goes through the
+    /// response filter like any other response. `validate` ignores the
+    /// fallback and still reports unknown commands. `None` removes it.
+    /// The handler must be `Send`, like the parser it is installed in (see
+    /// `sync::SyncAtParser`).
+    pub fn set_fallback(&mut self, fallback: Option<&'a mut (dyn AtFallback + Send)>) {
+        self.fallback = fallback;
+    }
+
+    /// Install a handler for the bare `AT` ping, e.g. to blink an LED on
+    /// every ping; its `exec` answers it. `None` removes it.
+    ///
+    /// Precedence for a bare `AT`: a command registered under the name
+    /// `AT` first, then this handler, then the fallback (`set_fallback`),
+    /// and finally the built-in answer, an empty success (`OK` on the framed
+    /// paths). Only the Exec form is a ping: `AT?` or `AT=1` are syntax
+    /// errors. The answer goes through the response filter, the history and
+    /// the observer like a fallback response. The handler must be `Send`,
+    /// like the fallback.
+    pub fn set_ping_handler(&mut self, ping: Option<&'a mut (dyn AtContext + Send)>) {
+        self.ping = ping;
+    }
+
+    /// Look up a registered command and get its handler, to invoke a form
+    /// directly (e.g. from an internal event) without parsing a line
+    ///
+    /// `name` is matched exactly like the command name of an input line,
+    /// indexed entries included (`AT+SOCK1` returns the `AT+SOCK#` handler,
+    /// the index is not kept). Nothing goes through the parser hooks:
+    /// filters, defaults and the history are bypassed. The handler is the
+    /// same module the table borrows, so the parser stays mutably borrowed
+    /// while the handle is alive and any state change is seen by later
+    /// commands.
+    pub fn command_mut(&mut self, name: &str) -> Option<&mut T> {
+        self.find_mut(name).map(|(_, module, _)| module)
+    }
+
+    /// Number of registered commands, in every table alike
+    /// Each entry counts once, so an indexed entry (`AT+SOCK#`) is a single
+    /// command whatever the number of instances it serves.
+    pub fn len(&self) -> usize {
+        self.entries().count()
+    }
+
+    /// Check if no command is registered
+    pub fn is_empty(&self) -> bool {
+        self.entries().next().is_none()
+    }
+
+    /// Iterate over the names of all registered commands, in lookup order:
+    /// main table, secondary table, then groups in registration order
+    pub fn command_names(&self) -> impl Iterator<Item = &'a str> {
+        self.entries().map(|(name, _)| *name)
+    }
+
+    /// Iterate over the registered command names starting with `partial`
+    /// (e.g. "AT+WI" yields "AT+WIFI", "AT+WIFISCAN"), for host-side
+    /// autocompletion. Matching is case-sensitive and allocation-free.
+    pub fn complete<'p>(&'p self, partial: &'p str) -> impl Iterator<Item = &'a str> + 'p {
+        self.command_names().filter(move |name| name.starts_with(partial))
+    }
+
+    /// Check the registered commands for misconfiguration, e.g. once at
+    /// boot
+    ///
+    /// Every table is checked (main, secondary and groups) against:
+    /// 1. Empty names, which no input can match
+    /// 2. Names longer than `MAX_NAME_LEN`, as registered (an indexed entry
+    ///    counts its `#`)
+    /// 3. Names registered more than once, in the same table or across
+    ///    tables: only the first in lookup order is reachable
+    ///
+    /// Read-only; the first problem found, in lookup order, is returned.
+    ///
+    /// # Returns
+    /// * `Ok(())` - The tables are consistent
+    /// * `Err(ConfigError)` - The first misconfiguration found
+    pub fn self_check(&self) -> Result<(), ConfigError<'a>> {
+        check_names(|| self.command_names())
+    }
+
+    /// Apply the input normalizer, if any, and trim the line
+    fn normalize<'i>(&self, input: &'i str) -> &'i str {
+        let input = match self.normalizer {
+            Some(normalizer) => normalizer(input),
+            None => input,
+        };
+        let terminators = self.line_terminators;
+        input
+            .trim_start()
+            .trim_end_matches(|c: char| c.is_whitespace() || terminators.contains(&c))
+    }
+
+    /// Suggest the registered command closest to a mistyped input, for
+    /// "did you mean" diagnostics
+    ///
+    /// Compares the command name of `input` (form suffix and arguments are
+    /// ignored) with every registered name, after their `AT` prefixes, and
+    /// returns the one sharing the longest ASCII case-insensitive prefix;
+    /// the first registered wins ties. `None` when no name shares even its
+    /// first character. Allocation-free, linear in the table size.
+    pub fn suggest(&self, input: &str) -> Option<&'a str> {
+        let input = input.trim();
+        let name = parse_trimmed(input, self.set_delimiter).map_or(input, |(name, _)| name);
+        let name = command_suffix(name).as_bytes();
+
+        let mut best = None;
+        let mut best_len = 0;
+        for candidate in self.command_names() {
+            let len = command_suffix(candidate)
+                .bytes()
+                .zip(name.iter())
+                .take_while(|(a, b)| a.eq_ignore_ascii_case(b))
+                .count();
+            if len > best_len {
+                best = Some(candidate);
+                best_len = len;
+            }
+        }
+        best
+    }
+
+    /// Iterate over all registered commands: main table, secondary table,
+    /// then groups
+    fn entries(&self) -> impl Iterator<Item = &(&'a str, &'a mut T)> {
+        self.commands
+            .iter()
+            .chain(self.secondary.iter())
+            .chain(self.groups.iter().flatten().flat_map(|g| g.iter()))
+    }
+
+    /// Iterate mutably over all registered commands, in `entries` order
+    fn entries_mut(&mut self) -> impl Iterator<Item = &mut (&'a str, &'a mut T)> {
+        self.commands
+            .iter_mut()
+            .chain(self.secondary.iter_mut())
+            .chain(self.groups.iter_mut().flatten().flat_map(|g| g.iter_mut()))
+    }
+
+    /// Parse a normalized input line and apply the parser-level checks
+    fn parse_checked<'i>(&self, input: &'i str) -> Result<(&'i str, AtForm<'i>), AtError> {
+        let (mut name, mut form) = parse_trimmed(input, self.set_delimiter)?;
+        if self.space_args
+            && let AtForm::Exec = form
+            && let Some((cmd, args)) = name.split_once(' ') {
+            name = cmd;
+            form = AtForm::Set(Args { raw: args.trim_start() });
+        }
+        if let AtForm::Set(args) = &form
+            && args.raw.split(',').nth(self.max_args).is_some() {
+            return Err(AtError::InvalidArgs);
+        }
+        Ok((name, form))
+    }
+
+    /// Run the fallback handler for an unmatched command, answering the
+    /// bare `AT` ping first (see `set_ping_handler`)
+    fn run_fallback(&mut self, input: &str, name: &str, kind: FormKind) -> AtResult<'static> {
+        let ping = name == "AT" && kind == FormKind::Exec;
+        if ping && let Some(handler) = self.ping.as_deref() {
+            return handler.exec();
+        }
+        match self.fallback.as_mut() {
+            Some(fallback) => fallback.handle(input, name, kind),
+            None if ping => Ok(""),
+            None => Err(AtError::UnknownCommand),
+        }
+    }
+
+    /// Apply the rename registered for `name`, if any (see `add_remap`)
+    fn remapped<'n>(&self, name: &'n str) -> &'n str
+    where
+        'a: 'n {
+        self.remaps
+            .iter()
+            .flatten()
+            .find(|(old, _)| *old == name)
+            .map_or(name, |(_, new)| new)
+    }
+
+    /// Find the handler registered under `name`, with its registered name,
+    /// without borrowing it mutably
+    fn find(&self, name: &str) -> Option<(&'a str, &T)> {
+        let name = self.remapped(name);
+        if name.len() > self.max_name_len {
+            return None;
+        }
+        self.entries()
+            .find(|(n, _)| match_name(n, name).is_some())
+            .map(|(n, module)| (*n, &**module))
+    }
+
+    /// Find the handler registered under `name`, with its registered name
+    fn find_mut(&mut self, name: &str) -> Option<(&'a str, &mut T, Option<u32>)> {
+        let name = self.remapped(name);
+        if name.len() > self.max_name_len {
+            return None;
+        }
+        self.entries_mut()
+            .find_map(|(n, module)| match_name(n, name).map(|index| (*n, &mut **module, index)))
+    }
+
+    /// Prepare a trimmed input line for dispatch: apply the empty-line
+    /// policy, parse it, run the parser-level checks and look up its handler
+    ///
+    /// `arity` reports the module's declared arity for strict argument checks;
+    /// `state_mask` reports the device states the module is allowed in;
+    /// `read_only` reports whether the module rejects the Set form.
+    fn prepare<'i>(&mut self, input: &'i str, arity: fn(&T) -> Option<usize>, state_mask: fn(&T) -> u32, read_only: fn(&T) -> bool) -> Result<Prepared<'i, 'a, '_, T>, AtError> {
+        if input.is_empty() && self.ignore_empty {
+            return Ok(Prepared::Ignored);
+        }
+        self.check_line(input)?;
+        let (name, form) = self.parse_checked(input)?;
+
+        let strict = self.strict_args;
+        let state = self.state;
+        let Some((name, module, index)) = self.find_mut(name) else {
+            return Ok(Prepared::Unmatched { name, form });
+        };
+        check_module(module, &form, state, strict, arity, state_mask, read_only)?;
+        Ok(Prepared::Command { name, form, module, index })
+    }
+
+    /// Parser-level checks of a whole line, before it is parsed
+    fn check_line(&self, input: &str) -> Result<(), AtError> {
+        if self.busy {
+            return Err(AtError::Busy);
+        }
+        if self.ascii_only && !input.is_ascii() {
+            return Err(AtError::InvalidEncoding);
+        }
+        Ok(())
+    }
+
+    /// Record the outcome of a command in the history and report it to the
+    /// observer
+    fn record(&mut self, input: &str, name: Option<&'a str>, result: &AtResult<'static>) {
+        #[cfg(feature = "history")]
+        if let Some(history) = self.history.as_mut() {
+            history.record(HistoryEntry { name, code: result.err().map_or(0, |e| e.code()) });
+        }
+        if let Some(observer) = self.observer {
+            observer(input, name, &unsuppress(*result));
+        }
+    }
+}
+
+/// Input line ready for dispatch (see `AtParser::prepare`)
+enum Prepared<'i, 'n, 'm, T> {
+    /// Empty line ignored by the empty-line policy
+    Ignored,
+    /// A registered command matched
+    Command {
+        /// Registered command name
+        name: &'n str,
+        /// Parsed form
+        form: AtForm<'i>,
+        /// Command handler
+        module: &'m mut T,
+        /// Numeric suffix matched by an indexed (`#`) entry
+        index: Option<u32>,
+    },
+    /// No registered command matches; goes to the fallback
+    Unmatched {
+        /// Command name as sent
+        name: &'i str,
+        /// Parsed form
+        form: AtForm<'i>,
+    },
+}
+
+impl<'a, T> AtParser<'a, T>
+where
+    T: AtContext {
+
+    /// Check an AT command string without executing it
+    ///
+    /// Performs the same parsing, lookup and parser-level checks as
+    /// `execute` (busy, ASCII-only, state mask, read-only and strict
+    /// arguments) but never invokes the command handler, so no module
+    /// state is changed. What only happens at execution is skipped: the
+    /// empty-line policy, the bare `AT` ping and the fallback, so such lines
+    /// are reported as `AtError::UnknownCommand`, as well as the errors the
+    /// handler itself would return.
+    ///
+    /// # Arguments
+    /// * `input` - The raw AT command string (e.g., "AT+CMD=1")
+    ///
+    /// # Returns
+    /// * `Ok((name, kind))` - The registered command name and the parsed form
+    /// * `Err(AtError)` - The error `execute` would report for the input
+    ///   before running the handler
+    pub fn validate(&self, input: &str) -> Result<(&'a str, FormKind), AtError> {
+        let input = self.normalize(input);
+        self.check_line(input)?;
+        let (name, form) = self.parse_checked(input)?;
+        let (name, module) = self.find(name).ok_or(AtError::UnknownCommand)?;
+        check_module(module, &form, self.state, self.strict_args, T::arity, T::state_mask, T::read_only)?;
+        Ok((name, form.kind()))
     }
 
     /// Parse and execute an AT command string
+    ///
+    /// A suppressed response (`SUPPRESSED`) is returned as `Ok("")`.
     /// 
     /// # Arguments
     /// * `input` - The raw AT command string (e.g., "AT+CMD?")
@@ -72,43 +1178,997 @@ where
     /// * `Ok(&str)` - Success response from the command handler
     /// * `Err(AtError)` - Error if parsing fails or command is not found
     pub fn execute(&mut self, input: &str) -> AtResult<'static> {
-        let input = input.trim();
-        let (name, form) = parse(input)?;
+        unsuppress(self.execute_marked(input))
+    }
 
-        // Find the command handler
-        let (_, module) = self.commands
-            .iter_mut()
-            .find(|(n, _)| *n == name)
-            .ok_or(AtError::UnknownCommand)?;
+    /// `execute`, keeping the `SUPPRESSED` marker for the framed paths
+    fn execute_marked(&mut self, input: &str) -> AtResult<'static> {
+        let input = self.normalize(input);
+        let filter = self.response_filter;
+        let defaults = self.form_defaults;
+
+        let (name, result) = match self.prepare(input, T::arity, T::state_mask, T::read_only) {
+            Ok(Prepared::Ignored) => return Ok(""),
+            Ok(Prepared::Command { name, form, module, index }) => {
+                let kind = form.kind();
+                let result = dispatch_form(module, form, index);
+                (Some(name), if index.is_none() { apply_default(module, kind, defaults, result) } else { result })
+            }
+            Ok(Prepared::Unmatched { name, form }) => (None, self.run_fallback(input, name, form.kind())),
+            Err(e) => (None, Err(e)),
+        };
 
-        // Dispatch to the appropriate handler method
-        match form {
-            AtForm::Exec => module.exec(),
-            AtForm::Query => module.query(),
-            AtForm::Test => module.test(),
-            AtForm::Set(args) => module.set(args),
+        let result = apply_filter(filter, result);
+        self.record(input, name, &result);
+        result
+    }
+
+    /// Parse and execute an AT command string, passing a shared context
+    /// (e.g. peripheral handles) to the handler
+    ///
+    /// Behaves like `execute` but dispatches through `AtContextWith`, so
+    /// modules receive `ctx` instead of storing peripheral pointers.
+    ///
+    /// # Arguments
+    /// * `input` - The raw AT command string (e.g., "AT+CMD?")
+    /// * `ctx` - Shared context handed to the handler
+    ///
+    /// # Returns
+    /// * `Ok(&str)` - Success response from the command handler
+    /// * `Err(AtError)` - Error if parsing fails or command is not found
+    pub fn execute_with<C>(&mut self, input: &str, ctx: &mut C) -> AtResult<'static>
+    where
+        T: AtContextWith<C>,
+        C: ?Sized {
+        let input = self.normalize(input);
+        let filter = self.response_filter;
+        let defaults = self.form_defaults;
+
+        let (name, result) = match self.prepare(input, T::arity, T::state_mask, T::read_only) {
+            Ok(Prepared::Ignored) => return Ok(""),
+            Ok(Prepared::Command { name, form, module, index: Some(index) }) => {
+                (Some(name), dispatch_form(module, form, Some(index)))
+            }
+            Ok(Prepared::Command { name, form, module, index: None }) => {
+                let kind = form.kind();
+                let result = match form {
+                    AtForm::Exec => module.exec_ctx(ctx),
+                    #[cfg(feature = "query")]
+                    AtForm::Query => module.query_ctx(ctx),
+                    #[cfg(feature = "test")]
+                    AtForm::Test => module.test_ctx(ctx),
+                    #[cfg(feature = "test")]
+                    AtForm::TestWith(args) => module.test_with(args),
+                    AtForm::Set(args) => module.validate_set(args).and_then(|()| module.set_ctx(args, ctx)),
+                };
+                (Some(name), apply_default(module, kind, defaults, result))
+            }
+            Ok(Prepared::Unmatched { name, form }) => (None, self.run_fallback(input, name, form.kind())),
+            Err(e) => (None, Err(e)),
+        };
+
+        let result = apply_filter(filter, result);
+        self.record(input, name, &result);
+        unsuppress(result)
+    }
+
+    /// Parse and execute an AT command received as raw bytes
+    ///
+    /// # Arguments
+    /// * `input` - The raw AT command bytes (e.g., b"AT+CMD?")
+    ///
+    /// # Returns
+    /// * `Ok(&str)` - Success response from the command handler
+    /// * `Err(AtError::LineTooLong)` - The input exceeds the maximum line length
+    /// * `Err(AtError::InvalidEncoding)` - The input is not valid UTF-8
+    /// * `Err(AtError)` - Any error `execute` reports
+    pub fn execute_bytes(&mut self, input: &[u8]) -> AtResult<'static> {
+        let input = self.decode(input)?;
+        self.execute(input)
+    }
+
+    /// Check the length and encoding of a raw input line
+    fn decode<'i>(&self, input: &'i [u8]) -> Result<&'i str, AtError> {
+        if input.len() > self.max_line_len {
+            return Err(AtError::LineTooLong);
+        }
+        core::str::from_utf8(input).map_err(|_| AtError::InvalidEncoding)
+    }
+
+    /// Parse and execute an AT command string, writing the framed response
+    /// into a byte buffer (see `execute_framed` for the layout)
+    ///
+    /// # Arguments
+    /// * `input` - The raw AT command string (e.g., "AT+CMD?")
+    /// * `buf` - Destination buffer for the framed response
+    ///
+    /// # Returns
+    /// * `Ok(usize)` - Number of bytes written (command errors included)
+    /// * `Err(AtError::BufferFull)` - The framed response does not fit in `buf`
+    ///
+    /// Unlike `execute_framed`, binary payloads (`Response::Bytes`) are
+    /// copied verbatim, whatever their encoding.
+    pub fn execute_into(&mut self, input: &str, buf: &mut [u8]) -> Result<usize, AtError> {
+        let mut out = ResponseWriter::new(buf);
+        if self.normalize(input).is_empty() && self.ignore_empty {
+            return Ok(0);
+        }
+        match self.write_payload(input, &mut out).map_err(|_| AtError::BufferFull)? {
+            Ok(Response::Bytes(bytes)) => out.write_bytes(bytes)
+                .and_then(|()| out.write_str("\r\n"))
+                .and_then(|()| self.write_ok(&mut out)),
+            status => self.write_status(status, &mut out),
+        }.map_err(|_| AtError::BufferFull)?;
+        Ok(out.len())
+    }
+
+    /// Parse and execute an AT command received as raw bytes, writing a
+    /// compact binary response into a byte buffer
+    ///
+    /// Non-standard extension for proprietary transports on very constrained
+    /// links: the response is a single result byte followed by the payload
+    /// of a successful command, verbatim (no prefix, no `\r\n`). The
+    /// transport is expected to frame it, so the payload runs to the end of
+    /// the frame. The result byte is `0x00` for success, otherwise
+    /// `AtError::code` truncated to a byte:
+    ///
+    /// | Byte | Error |
+    /// |------|-------|
+    /// | `0x01` | `UnknownCommand` |
+    /// | `0x02` | `NotSupported` |
+    /// | `0x03` | `InvalidArgs` |
+    /// | `0x04` | `LineTooLong` |
+    /// | `0x05` | `BufferFull` |
+    /// | `0x06` | `InvalidEncoding` |
+    /// | `0x07` | `MissingArg` |
+    /// | `0x08` | `SyntaxAt` (the offset is dropped) |
+    /// | `0x09` | `Busy` |
+    /// | `0x0A` | `ReadOnly` |
+    /// | `0x64` | `Message` (the text is dropped) |
+    ///
+    /// The command runs through `execute_bytes`, so the response filter
+    /// applies while the response prefix, quiet mode and the error formatter
+    /// do not. Ignored empty lines and suppressed responses (`SUPPRESSED`)
+    /// write nothing.
+    ///
+    /// # Arguments
+    /// * `input` - The raw AT command bytes (e.g., b"AT+CMD?")
+    /// * `buf` - Destination buffer for the result byte and the payload
+    ///
+    /// # Returns
+    /// * `Ok(usize)` - Number of bytes written (command errors included)
+    /// * `Err(AtError::BufferFull)` - The response does not fit in `buf`
+    pub fn execute_bytes_framed(&mut self, input: &[u8], buf: &mut [u8]) -> Result<usize, AtError> {
+        if input.len() <= self.max_line_len
+            && let Ok(text) = core::str::from_utf8(input)
+            && self.normalize(text).is_empty()
+            && self.ignore_empty {
+            return Ok(0);
+        }
+        let (code, payload) = match self.decode(input).and_then(|input| self.execute_marked(input)) {
+            Ok(SUPPRESSED) => return Ok(0),
+            Ok(payload) => (0, payload),
+            Err(e) => (e.code() as u8, ""),
+        };
+        let mut out = ResponseWriter::new(buf);
+        out.write_bytes(&[code])
+            .and_then(|()| out.write_str(payload))
+            .map_err(|_| AtError::BufferFull)?;
+        Ok(out.len())
+    }
+
+    /// Parse and execute an AT command string, collecting the framed
+    /// response line by line (see `execute_framed` for the layout)
+    ///
+    /// Every payload line and the final terminator (`OK`, or the error line
+    /// of a failed command) becomes one string, without its `\r\n`. Only
+    /// available with the `heapless` feature.
+    ///
+    /// # Capacity
+    /// * `N` - Maximum length in bytes of a single line
+    /// * `M` - Maximum number of lines, terminator included
+    ///
+    /// # Returns
+    /// * `Ok(lines)` - The response lines (command errors included)
+    /// * `Err(AtError::BufferFull)` - A line is longer than `N` bytes or the
+    ///   response has more than `M` lines
+    #[cfg(feature = "heapless")]
+    pub fn execute_lines<const N: usize, const M: usize>(&mut self, input: &str) -> Result<heapless::Vec<heapless::String<N>, M>, AtError> {
+        let mut out = LineCollector { lines: heapless::Vec::new(), line: heapless::String::new(), cr: false };
+        self.execute_framed(input, &mut out).map_err(|_| AtError::BufferFull)?;
+        if out.cr {
+            out.line.push('\r').map_err(|_| AtError::BufferFull)?;
+        }
+        if !out.line.is_empty() {
+            out.lines.push(out.line).map_err(|_| AtError::BufferFull)?;
+        }
+        Ok(out.lines)
+    }
+
+    /// Parse and execute an AT command string, writing the framed response
+    ///
+    /// The handler result is rendered as follows:
+    /// * `Response::None` (empty payload) - `OK\r\n`
+    /// * `Response::Text(payload)` - `payload\r\nOK\r\n`
+    /// * `Response::Bytes(payload)` - like `Text` when the bytes are valid
+    ///   UTF-8, otherwise the `AtError::InvalidEncoding` error line (use
+    ///   `execute_into` for a channel carrying raw bytes)
+    /// * `Response::Intermediate(code)` - `payload\r\n` if any, then
+    ///   `code\r\n` (e.g. `CONNECT`) and the data phase starts (see
+    ///   `feed_data`)
+    /// * `Err(AtError)` - `ERROR\r\n` (`ERROR: <message>\r\n` for
+    ///   `AtError::Message`), or the line of the error formatter
+    ///
+    /// Handlers that have nothing to report should return `Ok("")` instead of
+    /// `Ok("OK")`, otherwise the terminator is emitted twice.
+    ///
+    /// Empty lines write nothing unless `set_ignore_empty(false)` was called.
+    ///
+    /// # Arguments
+    /// * `input` - The raw AT command string (e.g., "AT+CMD?")
+    /// * `out` - Destination for the framed response
+    ///
+    /// # Returns
+    /// * `Ok(())` - The response was written (command errors included)
+    /// * `Err(fmt::Error)` - The writer failed
+    pub fn execute_framed(&mut self, input: &str, out: &mut dyn Write) -> core::fmt::Result {
+        if self.normalize(input).is_empty() && self.ignore_empty {
+            return Ok(());
+        }
+        let status = self.write_payload(input, out)?;
+        self.write_status(status, out)
+    }
+
+    /// Check whether a data phase is open (see `feed_data`)
+    pub fn data_mode(&self) -> bool {
+        self.data_target.is_some()
+    }
+
+    /// Route the bytes of the open data phase to its command
+    ///
+    /// State transitions:
+    /// 1. Command mode: a successful command whose module answers
+    ///    `AtContext::data_phase` with a code writes that code (e.g.
+    ///    `CONNECT`) instead of `OK` on the framed paths, and the parser
+    ///    enters data mode. The rest of an `execute_line` batch is dropped.
+    /// 2. Data mode: the caller hands every received byte to this method
+    ///    instead of `execute_*`, checking `data_mode` to route its input;
+    ///    the parser passes them to `AtContext::data` of the same command.
+    /// 3. When `data` returns the final result, its payload line and the
+    ///    terminator (`OK` or the error line) are written and the parser
+    ///    goes back to command mode. Receiving the escape sequence (see
+    ///    `set_escape_sequence`) also goes back to command mode, writing
+    ///    `OK`.
+    ///
+    /// Bytes fed outside data mode are ignored. Quiet mode suppresses the
+    /// intermediate and final result codes like any other. Data phases are
+    /// not recorded in the history.
+    ///
+    /// # Arguments
+    /// * `bytes` - Bytes received during the data phase
+    /// * `out` - Destination for the final response
+    ///
+    /// # Returns
+    /// * `Ok(())` - The bytes were handled
+    /// * `Err(fmt::Error)` - The writer failed
+    pub fn feed_data(&mut self, bytes: &[u8], out: &mut dyn Write) -> core::fmt::Result {
+        if self.data_target.is_none() {
+            return Ok(());
+        }
+        if let Some(escape) = self.escape {
+            let matched = self.escape_matched;
+            if !bytes.is_empty() && escape[matched..].starts_with(bytes) {
+                self.escape_matched += bytes.len();
+                if self.escape_matched < escape.len() {
+                    return Ok(());
+                }
+                self.escape_matched = 0;
+                self.data_target = None;
+                return self.write_ok(out);
+            }
+            // Not an escape after all: the held-back prefix is data
+            self.escape_matched = 0;
+            if matched > 0 && self.deliver_data(&escape[..matched], out)? {
+                return Ok(());
+            }
+        }
+        self.deliver_data(bytes, out).map(drop)
+    }
+
+    /// Hand data-phase bytes to the command of the open data phase, ending
+    /// the phase with its final response when it returns one
+    ///
+    /// # Returns
+    /// * `Ok(true)` - The data phase ended
+    /// * `Ok(false)` - The data phase goes on
+    /// * `Err(fmt::Error)` - The writer failed
+    fn deliver_data(&mut self, bytes: &[u8], out: &mut dyn Write) -> Result<bool, core::fmt::Error> {
+        let Some(target) = self.data_target else {
+            return Ok(true);
+        };
+        let result = match self.find_mut(target) {
+            Some((_, module, _)) => match module.data(bytes) {
+                Some(result) => result,
+                None => return Ok(false),
+            },
+            // The command was unregistered during the data phase
+            None => Err(AtError::UnknownCommand),
+        };
+        self.data_target = None;
+        let status = write_result(result, None, out)?;
+        self.write_status(status, out).map(|()| true)
+    }
+
+    /// Describe every registered command for host tooling
+    ///
+    /// Writes one `name\thelp\n` line per command, in `command_names`
+    /// order, with the text of `AtContext::help`. Tabs and line breaks inside
+    /// the help text are written as spaces so each command stays on a single
+    /// line. Allocation-free.
+    ///
+    /// # Arguments
+    /// * `out` - Destination for the description
+    ///
+    /// # Returns
+    /// * `Ok(())` - All commands were written
+    /// * `Err(fmt::Error)` - The writer failed
+    pub fn describe_into(&self, out: &mut dyn Write) -> core::fmt::Result {
+        for (name, module) in self.entries() {
+            write!(out, "{}\t", name)?;
+            for c in module.help().chars() {
+                out.write_char(if matches!(c, '\t' | '\r' | '\n') { ' ' } else { c })?;
+            }
+            out.write_char('\n')?;
+        }
+        Ok(())
+    }
+
+    /// Dump the state of every registered command, like `AT&V` on modems
+    ///
+    /// Calls `AtContext::query_iter` on each command in `command_names`
+    /// order and writes every row as `+NAME: value\r\n` (see
+    /// `set_response_prefix` for the `NAME` format), after the response
+    /// filter. No terminator is written. Commands answering `NotSupported`
+    /// are skipped, as are indexed (`#`) entries; rows of a command failing
+    /// with another error stop at that error and the dump goes on with the
+    /// next command. Commands are not recorded in the history.
+    ///
+    /// # Arguments
+    /// * `out` - Destination for the state dump
+    ///
+    /// # Returns
+    /// * `Ok(())` - The dump was written
+    /// * `Err(fmt::Error)` - The writer failed
+    #[cfg(feature = "query")]
+    pub fn query_all_into(&mut self, out: &mut dyn Write) -> core::fmt::Result {
+        let filter = self.response_filter;
+        for (name, module) in self.entries_mut().filter(|(name, _)| !name.ends_with('#')) {
+            let prefix = Prefix { name: command_suffix(name), index: None };
+            write_module(*module, AtForm::Query, None, filter, FormDefaults::default(), Some(prefix), out)?.ok();
+        }
+        Ok(())
+    }
+
+    /// Execute a batch of `;`-separated AT commands, writing the framed response
+    ///
+    /// Every command payload is written on its own line and a single final
+    /// `OK\r\n` is emitted once all commands succeeded. Execution stops at the
+    /// first failing command, which is reported as `ERROR\r\n` (or the line of
+    /// the error formatter).
+    ///
+    /// Empty segments (e.g. from `AT+A;;AT+B;`) are skipped silently. A line
+    /// that is entirely empty follows the `set_ignore_empty` policy.
+    ///
+    /// # Arguments
+    /// * `line` - The raw command line (e.g., "AT+A;AT+B=1")
+    /// * `out` - Destination for the framed response
+    ///
+    /// # Returns
+    /// * `Ok(())` - The response was written (command errors included)
+    /// * `Err(fmt::Error)` - The writer failed
+    pub fn execute_line(&mut self, line: &str, out: &mut dyn Write) -> core::fmt::Result {
+        if line.trim().is_empty() && self.ignore_empty {
+            return Ok(());
+        }
+        for segment in line.split(';').map(str::trim).filter(|s| !s.is_empty()) {
+            let status = match self.write_payload(segment, out)? {
+                Ok(Response::Bytes(bytes)) => write_bytes_line(bytes, out)?,
+                // The rest of the line is dropped once a data phase opens
+                status @ Ok(Response::Intermediate(_)) => return self.write_status(status, out),
+                status => status.map(drop),
+            };
+            if let Err(e) = status {
+                return self.write_error(e, out);
+            }
+        }
+        self.write_ok(out)
+    }
+
+    /// Write the error line of a failed command (nothing in quiet mode)
+    fn write_error(&self, error: AtError, out: &mut dyn Write) -> core::fmt::Result {
+        if self.quiet {
+            return Ok(());
+        }
+        match (self.error_formatter, error) {
+            (Some(formatter), error) => formatter(error, out),
+            (None, AtError::Message(message)) => write!(out, "{}: {}\r\n", self.error_keyword, message),
+            (None, _) => write!(out, "{}\r\n", self.error_keyword),
+        }
+    }
+
+    /// Write the success terminator (nothing in quiet mode)
+    fn write_ok(&self, out: &mut dyn Write) -> core::fmt::Result {
+        if self.quiet {
+            return Ok(());
+        }
+        write!(out, "{}\r\n", self.ok_keyword)
+    }
+
+    /// Write the end of a single framed command once its payload was
+    /// handled by `write_payload`: the binary payload line if any, then the
+    /// result code (the intermediate code of a data phase, nothing for a
+    /// suppressed response)
+    fn write_status(&self, status: Result<Response<'static>, AtError>, out: &mut dyn Write) -> core::fmt::Result {
+        match status {
+            Ok(Response::Suppressed) => Ok(()),
+            Ok(Response::Intermediate(_)) if self.quiet => Ok(()),
+            Ok(Response::Intermediate(code)) => write!(out, "{}\r\n", code),
+            Ok(Response::Bytes(bytes)) => match write_bytes_line(bytes, out)? {
+                Ok(()) => self.write_ok(out),
+                Err(e) => self.write_error(e, out),
+            },
+            Ok(_) => self.write_ok(out),
+            Err(e) => self.write_error(e, out),
         }
     }
+
+    /// Execute a single command and write its payload lines, without the
+    /// final result code (see `write_module` for query rows)
+    ///
+    /// A binary payload is left to the caller, which knows whether the
+    /// writer can carry raw bytes.
+    ///
+    /// # Returns
+    /// * `Ok(Ok(response))` - The command succeeded and its text payload was written
+    /// * `Ok(Err(AtError))` - The command failed
+    /// * `Err(fmt::Error)` - The writer failed
+    fn write_payload(&mut self, input: &str, out: &mut dyn Write) -> Result<Result<Response<'static>, AtError>, core::fmt::Error> {
+        let input = self.normalize(input);
+        let filter = self.response_filter;
+        let prefix = self.response_prefix;
+        let defaults = self.form_defaults;
+
+        let mut data_target = None;
+        let (name, status) = match self.prepare(input, T::arity, T::state_mask, T::read_only) {
+            Ok(Prepared::Ignored) => return Ok(Ok(Response::None)),
+            Ok(Prepared::Command { name, form, module, index }) => {
+                let prefix = prefix.then(|| Prefix { name: command_suffix(name), index });
+                let kind = form.kind();
+                let bytes = if index.is_none() { module.bytes(&form) } else { Err(AtError::NotSupported) };
+                let status = match bytes {
+                    Err(AtError::NotSupported) => write_module(module, form, index, filter, defaults, prefix, out)?,
+                    bytes => bytes.map(Response::Bytes),
+                };
+                // A data phase replaces the final terminator, so binary and
+                // suppressed responses cannot open one; only Exec and Set
+                // (`ATD`, `AT+CIPSEND=5`) start a session
+                let code = match status {
+                    Ok(Response::None | Response::Text(_)) if matches!(kind, FormKind::Exec | FormKind::Set) => module.data_phase(),
+                    _ => None,
+                };
+                match code {
+                    Some(code) => {
+                        data_target = Some(name);
+                        (Some(name), Ok(Response::Intermediate(code)))
+                    }
+                    None => (Some(name), status),
+                }
+            }
+            Ok(Prepared::Unmatched { name, form }) => {
+                let result = apply_filter(filter, self.run_fallback(input, name, form.kind()));
+                (None, write_result(result, None, out)?)
+            }
+            Err(e) => (None, Err(e)),
+        };
+
+        if data_target.is_some() {
+            self.data_target = data_target;
+        }
+        self.record(input, name, &status.map(|response| match response {
+            // Binary payloads, data phases and suppressed responses are
+            // reported as an empty success
+            Response::None | Response::Bytes(_) | Response::Intermediate(_) => "",
+            Response::Text(payload) => payload,
+            Response::Suppressed => "",
+        }));
+        Ok(status)
+    }
+}
+
+impl<'a, T> Default for AtParser<'a, T> {
+
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'a, T> AtParser<'a, T>
+where
+    T: AsyncAtContext {
+
+    /// Parse and execute an AT command string, awaiting the async handler
+    ///
+    /// Parsing, lookup and the parser checks (state mask, read-only, strict
+    /// arity) are identical to `execute`; only the dispatch differs, so
+    /// handlers can perform async I/O before answering.
+    ///
+    /// # Arguments
+    /// * `input` - The raw AT command string (e.g., "AT+CMD?")
+    ///
+    /// # Returns
+    /// * `Ok(&str)` - Success response from the command handler
+    /// * `Err(AtError)` - Error if parsing fails or command is not found
+    pub async fn execute_async(&mut self, input: &str) -> AtResult<'static> {
+        let input = self.normalize(input);
+        let filter = self.response_filter;
+
+        let (name, result) = match self.prepare(input, T::arity, T::state_mask, T::read_only) {
+            Ok(Prepared::Ignored) => return Ok(""),
+            // Indexed handlers only exist on the synchronous AtContext
+            Ok(Prepared::Command { name, index: Some(_), .. }) => (Some(name), Err(AtError::NotSupported)),
+            Ok(Prepared::Command { name, form, module, index: None }) => {
+                // Dispatch to the appropriate handler method
+                let result = match form {
+                    AtForm::Exec => module.exec().await,
+                    #[cfg(feature = "query")]
+                    AtForm::Query => module.query().await,
+                    #[cfg(feature = "test")]
+                    AtForm::Test => module.test().await,
+                    #[cfg(feature = "test")]
+                    AtForm::TestWith(args) => module.test_with(args).await,
+                    AtForm::Set(args) => module.set(args).await,
+                };
+                (Some(name), result)
+            }
+            Ok(Prepared::Unmatched { name, form }) => (None, self.run_fallback(input, name, form.kind())),
+            Err(e) => (None, Err(e)),
+        };
+
+        let result = apply_filter(filter, result);
+        self.record(input, name, &result);
+        unsuppress(result)
+    }
+}
+
+/// Dispatch a parsed form to a module and write its payload lines
+///
+/// Query commands go through `AtContext::query_iter`, so every row is
+/// written on its own line; rows produced before a failing row are kept.
+fn write_module<T>(module: &mut T, form: AtForm, index: Option<u32>, filter: Option<ResponseFilter>, defaults: FormDefaults, prefix: Option<Prefix>, out: &mut dyn Write) -> Result<Result<Response<'static>, AtError>, core::fmt::Error>
+where
+    T: AtContext {
+    match form {
+        #[cfg(feature = "query")]
+        AtForm::Query if index.is_none() => {
+            let mut unsupported = false;
+            for (i, row) in module.query_iter().enumerate() {
+                match apply_filter(filter, row) {
+                    Ok("") => {}
+                    Ok(SUPPRESSED) => return Ok(Ok(Response::Suppressed)),
+                    Ok(row) => write_line(prefix, row, out)?,
+                    Err(AtError::NotSupported) if i == 0 => {
+                        unsupported = true;
+                        break;
+                    }
+                    Err(e) => return Ok(Err(e)),
+                }
+            }
+            if unsupported {
+                let result = apply_default(module, FormKind::Query, defaults, Err(AtError::NotSupported));
+                return write_result(apply_filter(filter, result), prefix, out);
+            }
+            Ok(Ok(Response::None))
+        }
+        #[cfg(feature = "test")]
+        AtForm::Test if index.is_none() => match module.test_into(out) {
+            Ok(()) => Ok(Ok(Response::None)),
+            Err(AtError::NotSupported) => {
+                let result = module.test();
+                let result = apply_default(module, FormKind::Test, defaults, result);
+                write_result(apply_filter(filter, result), None, out)
+            }
+            Err(e) => Ok(Err(e)),
+        },
+        #[cfg(feature = "query")]
+        AtForm::Query => write_result(apply_filter(filter, dispatch_form(module, form, index)), prefix, out),
+        AtForm::Exec => write_result(apply_filter(filter, dispatch_form(module, form, index)), prefix, out),
+        form => {
+            let kind = form.kind();
+            let mut result = dispatch_form(module, form, index);
+            if index.is_none() {
+                result = apply_default(module, kind, defaults, result);
+            }
+            write_result(apply_filter(filter, result), None, out)
+        }
+    }
+}
+
+/// Writer splitting the framed output into `\r\n`-terminated lines (see
+/// `AtParser::execute_lines`)
+#[cfg(feature = "heapless")]
+struct LineCollector<const N: usize, const M: usize> {
+    /// Completed lines, without their `\r\n`
+    lines: heapless::Vec<heapless::String<N>, M>,
+    /// Line being written
+    line: heapless::String<N>,
+    /// A `\r` was written and not yet added to `line`, so a line of exactly
+    /// `N` bytes still fits before its `\r\n`
+    cr: bool,
+}
+
+#[cfg(feature = "heapless")]
+impl<const N: usize, const M: usize> Write for LineCollector<N, M> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        for c in s.chars() {
+            if c == '\n' && self.cr {
+                self.cr = false;
+                self.lines.push(core::mem::take(&mut self.line)).map_err(|_| core::fmt::Error)?;
+                continue;
+            }
+            if core::mem::replace(&mut self.cr, c == '\r') {
+                self.line.push('\r').map_err(|_| core::fmt::Error)?;
+            }
+            if c != '\r' {
+                self.line.push(c).map_err(|_| core::fmt::Error)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `+NAME: ` response prefix, `NAME` ending with the index of indexed commands
+#[derive(Clone, Copy)]
+struct Prefix<'n> {
+    /// Command name without its `AT` prefix
+    name: &'n str,
+    /// Numeric suffix matched by an indexed entry
+    index: Option<u32>,
+}
+
+impl core::fmt::Display for Prefix<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.index {
+            Some(index) => write!(f, "+{}{}: ", self.name.trim_end_matches('#'), index),
+            None => write!(f, "+{}: ", self.name),
+        }
+    }
+}
+
+/// Write one payload line, with the optional `+NAME: ` prefix
+fn write_line(prefix: Option<Prefix>, payload: &str, out: &mut dyn Write) -> core::fmt::Result {
+    match prefix {
+        Some(prefix) => write!(out, "{}{}\r\n", prefix, payload),
+        None => write!(out, "{}\r\n", payload),
+    }
+}
+
+/// Write a binary payload line to a text writer
+/// Bytes that are not valid UTF-8 cannot be written and are reported as
+/// `AtError::InvalidEncoding`, with nothing written.
+fn write_bytes_line(bytes: &[u8], out: &mut dyn Write) -> Result<Result<(), AtError>, core::fmt::Error> {
+    match core::str::from_utf8(bytes) {
+        Ok(text) => write_line(None, text, out).map(Ok),
+        Err(_) => Ok(Err(AtError::InvalidEncoding)),
+    }
+}
+
+/// Write the payload line of a single command result (no terminator)
+fn write_result(result: AtResult<'static>, prefix: Option<Prefix>, out: &mut dyn Write) -> Result<Result<Response<'static>, AtError>, core::fmt::Error> {
+    match result.map(Response::from) {
+        Ok(Response::Text(payload)) => write_line(prefix, payload, out).map(|()| Ok(Response::Text(payload))),
+        Ok(response) => Ok(Ok(response)),
+        Err(e) => Ok(Err(e)),
+    }
+}
+
+/// Replace a `NotSupported` query/test result with the enabled parser default
+fn apply_default<T>(module: &T, kind: FormKind, defaults: FormDefaults, result: AtResult<'static>) -> AtResult<'static>
+where
+    T: AtContext + ?Sized {
+    #[cfg(not(any(feature = "query", feature = "test")))]
+    let _ = (module, defaults);
+    match (result, kind) {
+        #[cfg(feature = "query")]
+        (Err(AtError::NotSupported), FormKind::Query) if defaults.query_from_exec => module.exec(),
+        #[cfg(feature = "test")]
+        (Err(AtError::NotSupported), FormKind::Test) if defaults.test_from_help => Ok(module.help()),
+        (result, _) => result,
+    }
+}
+
+/// Parser-level checks of a matched command against what its module
+/// declares (see `AtParser::prepare` for the callbacks)
+fn check_module<T>(module: &T, form: &AtForm, state: u8, strict: bool, arity: fn(&T) -> Option<usize>, state_mask: fn(&T) -> u32, read_only: fn(&T) -> bool) -> Result<(), AtError> {
+    if !state_allowed(state_mask(module), state) {
+        return Err(AtError::NotSupported);
+    }
+    if let AtForm::Set(_) = form
+        && read_only(module) {
+        return Err(AtError::ReadOnly);
+    }
+    if strict && let (AtForm::Set(args), Some(max)) = (form, arity(module)) {
+        args.expect(0, max)?;
+    }
+    Ok(())
+}
+
+/// Run a successful response through the optional response filter
+/// The `SUPPRESSED` marker is passed through untouched.
+fn apply_filter(filter: Option<ResponseFilter>, result: AtResult<'static>) -> AtResult<'static> {
+    match (filter, result) {
+        (Some(filter), Ok(payload)) if payload != SUPPRESSED => Ok(filter(payload)),
+        (_, result) => result,
+    }
+}
+
+/// Turn the `SUPPRESSED` marker into an empty success for the plain paths,
+/// which have no framed output to suppress
+fn unsuppress(result: AtResult<'static>) -> AtResult<'static> {
+    match result {
+        Ok(SUPPRESSED) => Ok(""),
+        result => result,
+    }
+}
+
+/// Execute an AT command string on a single module, without a parser
+///
+/// Meant for unit tests of an `AtContext`: the form is parsed from `input`
+/// like `AtParser::execute` does and the matching method is called
+/// (`validate_set` before `set`). No command table is involved, `name` is
+/// only the name `input` must start with, e.g. `"AT+LED"` for
+/// `"AT+LED=1"`; an indexed name (`"AT+SOCK#"`) calls the indexed handlers.
+/// None of the parser settings (delimiter, form defaults, filter...) apply.
+///
+/// # Arguments
+/// * `module` - The command handler under test
+/// * `name` - The command name the module would be registered under
+/// * `input` - The raw AT command string (e.g., "AT+LED=1")
+///
+/// # Returns
+/// * `Ok(&str)` - Success response from the command handler
+/// * `Err(AtError::UnknownCommand)` - `input` is not a form of `name`
+/// * `Err(AtError)` - The parse error or the handler error
+pub fn dispatch(module: &mut dyn AtContext, name: &str, input: &str) -> AtResult<'static> {
+    let (sent, form) = parse(input)?;
+    let index = match_name(name, sent).ok_or(AtError::UnknownCommand)?;
+    dispatch_form(module, form, index)
+}
+
+/// Measure the byte length of the framed response of `form` without
+/// rendering it, e.g. to reserve a transmit buffer before sending
+///
+/// The handler runs for real and its output goes through the same
+/// formatting logic as `AtParser::execute_framed` (multi-row queries,
+/// `test_into`, binary payloads copied verbatim as by
+/// `AtParser::execute_into`) into a counting sink, so the handler is
+/// called again when the command is then executed: measure idempotent
+/// forms (Query, Test) only, a Set would be applied twice. The length
+/// includes the payload lines and the `OK\r\n` terminator of the default
+/// settings; parser settings (response prefix, keywords, quiet mode, form
+/// defaults, filter) are not applied.
+///
+/// # Returns
+/// * `Ok(usize)` - Length in bytes of the framed response
+/// * `Err(AtError)` - The handler error (the response would be an error
+///   line)
+pub fn measure_response<T>(module: &mut T, form: AtForm) -> Result<usize, AtError>
+where
+    T: AtContext {
+    let mut sink = ByteCount(0);
+    let response = match module.bytes(&form) {
+        Err(AtError::NotSupported) => write_module(module, form, None, None, FormDefaults::default(), None, &mut sink)
+            .map_err(|_| AtError::BufferFull)?,
+        // Binary payloads are sent verbatim (see `AtParser::execute_into`)
+        Ok(bytes) => {
+            sink.0 += bytes.len() + "\r\n".len();
+            Ok(Response::Bytes(bytes))
+        }
+        Err(e) => Err(e),
+    };
+    match response {
+        Ok(Response::Suppressed) => Ok(sink.0),
+        Ok(_) => Ok(sink.0 + "OK\r\n".len()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Sink counting the bytes written to it (see `measure_response`)
+struct ByteCount(usize);
+
+impl Write for ByteCount {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.0 += s.len();
+        Ok(())
+    }
+}
+
+/// Dispatch a parsed form to the appropriate handler method, using the
+/// indexed handlers when an indexed entry matched
+fn dispatch_form<T>(module: &mut T, form: AtForm, index: Option<u32>) -> AtResult<'static>
+where
+    T: AtContext + ?Sized {
+    if let Some(index) = index {
+        return match form {
+            AtForm::Exec => module.exec_indexed(index),
+            #[cfg(feature = "query")]
+            AtForm::Query => module.query_indexed(index),
+            #[cfg(feature = "test")]
+            AtForm::Test => module.test_indexed(index),
+            #[cfg(feature = "test")]
+            AtForm::TestWith(_) => Err(AtError::NotSupported),
+            AtForm::Set(args) => module.set_indexed(index, args),
+        };
+    }
+    match form {
+        AtForm::Exec => module.exec(),
+        #[cfg(feature = "query")]
+        AtForm::Query => module.query(),
+        #[cfg(feature = "test")]
+        AtForm::Test => module.test(),
+        #[cfg(feature = "test")]
+        AtForm::TestWith(args) => module.test_with(args),
+        AtForm::Set(args) => module.validate_set(args).and_then(|()| module.set(args)),
+    }
+}
+
+/// Check command names for emptiness, length and duplicates, in order
+/// (see `AtParser::self_check`)
+/// `names` yields a fresh iterator over the names at each call.
+pub(crate) fn check_names<'n, I>(names: impl Fn() -> I) -> Result<(), ConfigError<'n>>
+where
+    I: Iterator<Item = &'n str> {
+    for (position, name) in names().enumerate() {
+        if name.is_empty() {
+            return Err(ConfigError::EmptyName);
+        }
+        if name.len() > MAX_NAME_LEN {
+            return Err(ConfigError::NameTooLong(name));
+        }
+        if names().take(position).any(|previous| previous == name) {
+            return Err(ConfigError::DuplicateName(name));
+        }
+    }
+    Ok(())
+}
+
+/// Check whether a command with the allowed-state mask `mask` may run in
+/// `state` (see `AtParser::set_state`)
+fn state_allowed(mask: u32, state: u8) -> bool {
+    1u32.checked_shl(u32::from(state)).map_or(mask == u32::MAX, |bit| mask & bit != 0)
+}
+
+/// Match a command name as sent against a registered entry name
+///
+/// Returns `Some(None)` for an exact match and `Some(Some(index))` when an
+/// indexed entry (`AT+SOCK#`) matches its stem followed by one or more ASCII
+/// digits fitting in a `u32` (`AT+SOCK0`, `AT+SOCK12`). Signs, spaces and
+/// an empty index never match.
+fn match_name(entry: &str, name: &str) -> Option<Option<u32>> {
+    if entry == name {
+        return Some(None);
+    }
+    let digits = name.strip_prefix(entry.strip_suffix('#')?)?;
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok().map(Some)
+}
+
+/// Longest input name `entry` can match (see `match_name`); unbounded for
+/// indexed entries since their index may carry leading zeros
+fn name_len_bound(entry: &str) -> usize {
+    if entry.ends_with('#') { usize::MAX } else { entry.len() }
 }
 
 /// Parse an AT command string into its name and form
-/// 
+///
+/// This is the standalone parser used by `AtParser`; it does not look up
+/// the command table. `AtParser` reports any `ParseError` as
+/// `AtError::SyntaxAt` with the same offset, while this function keeps the
+/// detailed reason.
+///
+/// Equals signs: the first `=` separates the name from the arguments, so
+/// `AT+CMD==1` (doubled) is rejected with `DoubledEquals` and `AT+=1`,
+/// `AT=1` or `=1` (no name) with `EmptyName`. An `=` later in the arguments
+/// (`AT+CMD=a=b`) is part of the argument text.
+///
+/// Question marks: a line ending with `=?` is a Test and one ending with
+/// `?` after numeric arguments is a parameterized Test (`AT+CMD=1?` gives
+/// `TestWith` with the arguments `1`). Any other argument text keeps its
+/// trailing `?` as a Set (`AT+SEND=hello?`), and a line ending with `?`
+/// without an `=` is a Query.
+///
+/// Whitespace: only the whitespace around the whole line is trimmed. The
+/// command name is the exact text between the start of the line and the
+/// form marker, so `AT+CMD =1` names `AT+CMD ` (matching no `AT+CMD`
+/// entry) and a custom name with inner spaces (`AT+MY CMD`) is kept whole.
+///
 /// # Arguments
 /// * `input` - The command string to parse
-/// 
+///
 /// # Returns
-/// A tuple of (command_name, command_form)
-fn parse<'a>(input: &'a str) -> Result<(&'a str, AtForm<'a>), AtError> {
-    let input = input.trim();
+/// * `Ok((name, form))` - The command name and its form
+/// * `Err(ParseError)` - The form the input looked like and why it was rejected
+pub fn parse<'a>(input: &'a str) -> Result<(&'a str, AtForm<'a>), ParseError> {
+    parse_trimmed(input.trim(), '=')
+}
+
+/// `parse` for a line whose outer whitespace is already trimmed, with a
+/// custom Set delimiter (see `AtParser::set_set_delimiter`); nothing inside
+/// the line is trimmed
+fn parse_trimmed(input: &str, delimiter: char) -> Result<(&str, AtForm<'_>), ParseError> {
+    let (name, form) = split_form(input, delimiter);
+
+    // A bare empty line (or `AT`) is an Exec, handled by the empty-line
+    // policy or the command table; other forms need an actual name, which
+    // is expected right after the prefix
+    if command_suffix(name).is_empty() && !matches!(form, AtForm::Exec) {
+        return Err(ParseError { form: form.kind(), reason: ParseErrorReason::EmptyName, offset: name.len() });
+    }
+
+    // `AT+CMD==1` would otherwise be a Set with the argument `=1`
+    if let AtForm::Set(args) = &form
+        && args.raw.starts_with(delimiter) {
+        let offset = name.len() + delimiter.len_utf8();
+        return Err(ParseError { form: FormKind::Set, reason: ParseErrorReason::DoubledEquals, offset });
+    }
+    #[cfg(feature = "test")]
+    if let AtForm::TestWith(args) = &form
+        && args.raw.starts_with(delimiter) {
+        let offset = name.len() + delimiter.len_utf8();
+        return Err(ParseError { form: FormKind::Test, reason: ParseErrorReason::DoubledEquals, offset });
+    }
+
+    Ok((name, form))
+}
 
+/// Split an input line into its name and form by looking at the form
+/// markers, `delimiter` separating the name from the Set arguments
+fn split_form(input: &str, delimiter: char) -> (&str, AtForm<'_>) {
     // Check suffixes to determine command form
-    if let Some(cmd) = input.strip_suffix("=?") {
-        Ok((cmd, AtForm::Test))
-    } else if let Some(cmd) = input.strip_suffix('?') {
-        Ok((cmd, AtForm::Query))
-    } else if let Some((cmd, args)) = input.split_once('=') {
-        Ok((cmd, AtForm::Set(Args { raw: args })))
-    } else {
-        Ok((input, AtForm::Exec))
-    }
-}
\ No newline at end of file
+    // Forms compiled out via cargo features fall through to Set/Exec
+    // The custom delimiter makes a Test too (`AT+CMD#?`)
+    #[cfg(feature = "test")]
+    if let Some(cmd) = input.strip_suffix("=?").or_else(|| input.strip_suffix('?')?.strip_suffix(delimiter)) {
+        return (cmd, AtForm::Test);
+    }
+
+    // Only numeric parameters make a parameterized Test, so free text
+    // ending with `?` (`AT+SEND=hello?`, a URL) stays a Set
+    #[cfg(feature = "test")]
+    if let Some((cmd, args)) = input.strip_suffix('?').and_then(|rest| rest.split_once(delimiter))
+        && is_test_with_args(args) {
+        return (cmd, AtForm::TestWith(Args { raw: args }));
+    }
+
+    if let Some((cmd, args)) = input.split_once(delimiter) {
+        return (cmd, AtForm::Set(Args { raw: args }));
+    }
+
+    #[cfg(feature = "query")]
+    if let Some(cmd) = input.strip_suffix('?') {
+        return (cmd, AtForm::Query);
+    }
+
+    (input, AtForm::Exec)
+}
+
+/// Whether the arguments of a `=<args>?` line are parameters of a Test:
+/// signed decimal numbers separated by commas, without quotes or text, and
+/// at least one digit
+#[cfg(feature = "test")]
+fn is_test_with_args(args: &str) -> bool {
+    args.bytes().any(|b| b.is_ascii_digit())
+        && args.bytes().all(|b| b.is_ascii_digit() || matches!(b, b',' | b' ' | b'+' | b'-'))
+}
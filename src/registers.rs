@@ -0,0 +1,68 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+use crate::AtError;
+
+/// Callback fired when a register value changes (register number, new value)
+pub type RegisterCallback = fn(n: u8, value: u32);
+
+/// Bank of `N` numeric S-registers (`ATS0`..`ATS<N-1>`)
+///
+/// The bank only stores values; persistence is delegated to the optional
+/// `on_change` callback (e.g. to write the register to flash).
+pub struct SRegisters<const N: usize> {
+    /// Current register values
+    values: [u32; N],
+    /// Callback fired on every actual value change
+    on_change: Option<RegisterCallback>,
+}
+
+impl<const N: usize> SRegisters<N> {
+
+    /// Create a bank with the given initial (e.g. restored) values
+    pub const fn new(values: [u32; N]) -> Self {
+        Self { values, on_change: None }
+    }
+
+    /// Register the persistence callback (`None` removes it)
+    /// It fires only when `set` changes a value, never for rewrites of the
+    /// same value and never for the initial values given to `new`.
+    pub fn on_change(&mut self, callback: Option<RegisterCallback>) {
+        self.on_change = callback;
+    }
+
+    /// Get the value of register `n`, or `None` if it does not exist
+    pub fn get(&self, n: u8) -> Option<u32> {
+        self.values.get(usize::from(n)).copied()
+    }
+
+    /// Set the value of register `n` (`ATSn=value`)
+    /// Returns `AtError::InvalidArgs` if the register does not exist
+    pub fn set(&mut self, n: u8, value: u32) -> Result<(), AtError> {
+        let slot = self.values.get_mut(usize::from(n)).ok_or(AtError::InvalidArgs)?;
+        if *slot != value {
+            *slot = value;
+            if let Some(callback) = self.on_change {
+                callback(n, value);
+            }
+        }
+        Ok(())
+    }
+}
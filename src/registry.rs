@@ -0,0 +1,79 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+
+//! Link-time command registration (requires the `linker_registry` feature)
+//!
+//! Modules declare their `AtCommand` descriptors next to their code with
+//! `at_register!`, which places them in the `at_commands` linker section.
+//! At startup `collect_registered` returns every descriptor of that section
+//! as one slice, ready for `AtCommandParser::new`, so no central
+//! registration list is needed.
+//!
+//! # Linker requirements
+//!
+//! The section is delimited with the `__start_at_commands` and
+//! `__stop_at_commands` symbols that GNU ld and LLVM lld define for any
+//! section whose name is a valid C identifier, on ELF targets only. A custom
+//! linker script must keep the section (`KEEP(*(at_commands))`) and provide
+//! both symbols. At least one command must be registered, otherwise the
+//! symbols are undefined and linking fails.
+//!
+//! Everything is `no_std` and allocation-free: descriptors are plain
+//! `static` items made of function pointers and string literals. All
+//! registered descriptors must share the same context type, since the
+//! section is read back as a single `[AtCommand<T>]` slice.
+
+use crate::command::AtCommand;
+
+/// Register a command descriptor in the `at_commands` linker section
+///
+/// ```ignore
+/// at_register!(LED: Device = AtCommand::named("AT+LED").query(led_query));
+/// ```
+#[macro_export]
+macro_rules! at_register {
+    ($name:ident : $ctx:ty = $command:expr) => {
+        #[used]
+        #[unsafe(link_section = "at_commands")]
+        static $name: $crate::command::AtCommand<$ctx> = $command;
+    };
+}
+
+unsafe extern "C" {
+    /// First byte of the `at_commands` section, defined by the linker
+    static __start_at_commands: u8;
+    /// End of the `at_commands` section, defined by the linker
+    static __stop_at_commands: u8;
+}
+
+/// Get every descriptor registered with `at_register!`, in link order
+///
+/// # Safety
+/// Every descriptor of the `at_commands` section must be an
+/// `AtCommand<T>` with this very `T`, i.e. all `at_register!` invocations
+/// of the firmware use the same context type.
+pub unsafe fn collect_registered<T>() -> &'static [AtCommand<T>] {
+    let start = (&raw const __start_at_commands).cast::<AtCommand<T>>();
+    let stop = (&raw const __stop_at_commands).cast::<AtCommand<T>>();
+    let len = (stop as usize - start as usize) / core::mem::size_of::<AtCommand<T>>();
+    // SAFETY: the linker symbols delimit the section, which only holds
+    // `AtCommand<T>` statics (guaranteed by the caller)
+    unsafe { core::slice::from_raw_parts(start, len) }
+}
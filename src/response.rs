@@ -0,0 +1,374 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+use core::fmt::Write;
+
+/// Text representation of boolean values in responses (see
+/// `AtParser::set_bool_style`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BoolStyle {
+    /// `1` / `0` (default, modem style)
+    #[default]
+    ZeroOne,
+    /// `ON` / `OFF`
+    OnOff,
+    /// `true` / `false`
+    TrueFalse,
+}
+
+impl BoolStyle {
+
+    /// Get the text of `value` in this style
+    pub const fn text(self, value: bool) -> &'static str {
+        match (self, value) {
+            (BoolStyle::ZeroOne, true) => "1",
+            (BoolStyle::ZeroOne, false) => "0",
+            (BoolStyle::OnOff, true) => "ON",
+            (BoolStyle::OnOff, false) => "OFF",
+            (BoolStyle::TrueFalse, true) => "true",
+            (BoolStyle::TrueFalse, false) => "false",
+        }
+    }
+}
+
+/// Alignment of a fixed-width field (see `ResponseWriter::field_padded`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    /// Value first, padding after (`ab  `)
+    Left,
+    /// Padding first, value after (`  ab`)
+    Right,
+}
+
+/// Response writer backed by a caller-provided byte buffer
+/// Implements `core::fmt::Write` and fails with `fmt::Error` once the
+/// buffer is full, without allocating.
+pub struct ResponseWriter<'b> {
+    /// Destination buffer
+    buf: &'b mut [u8],
+    /// Number of bytes written so far
+    len: usize,
+}
+
+impl<'b> ResponseWriter<'b> {
+
+    /// Create a new writer over an empty buffer
+    pub fn new(buf: &'b mut [u8]) -> Self {
+        Self { buf, len: 0 }
+    }
+
+    /// Number of bytes written so far
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if nothing has been written yet
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the written bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    /// Get the written text
+    /// Empty when `write_bytes` wrote bytes that are not valid UTF-8.
+    pub fn as_str(&self) -> &str {
+        core::str::from_utf8(self.as_bytes()).unwrap_or_default()
+    }
+
+    /// Write raw bytes, with no UTF-8 requirement
+    /// Fails with `fmt::Error`, writing nothing, when they do not fit.
+    pub fn write_bytes(&mut self, bytes: &[u8]) -> core::fmt::Result {
+        let end = self.len + bytes.len();
+        if end > self.buf.len() {
+            return Err(core::fmt::Error);
+        }
+        self.buf[self.len..end].copy_from_slice(bytes);
+        self.len = end;
+        Ok(())
+    }
+
+    /// Write a boolean in the given style, usually the parser setting (see
+    /// `AtParser::bool_style`)
+    pub fn write_bool(&mut self, value: bool, style: BoolStyle) -> core::fmt::Result {
+        self.write_str(style.text(value))
+    }
+
+    /// Write an integer followed by its unit suffix (e.g. `1013hPa`)
+    pub fn value_with_unit_i32(&mut self, value: i32, unit: &str) -> core::fmt::Result {
+        write!(self, "{}{}", value, unit)
+    }
+
+    /// Write a fixed-point value followed by its unit suffix
+    ///
+    /// `value` is scaled by `10^decimals`: `(235, 1, "C")` writes `23.5C` and
+    /// `(-5, 2, "V")` writes `-0.05V`. The fractional part is always padded
+    /// to `decimals` digits and no rounding takes place. With `decimals == 0`
+    /// this is the same as `value_with_unit_i32`.
+    pub fn fixed_with_unit(&mut self, value: i32, decimals: u8, unit: &str) -> core::fmt::Result {
+        if decimals == 0 {
+            return self.value_with_unit_i32(value, unit);
+        }
+        let scale = 10u64.pow(u32::from(decimals.min(19)));
+        let abs = u64::from(value.unsigned_abs());
+        let sign = if value < 0 { "-" } else { "" };
+        write!(
+            self,
+            "{}{}.{:0width$}{}",
+            sign,
+            abs / scale,
+            abs % scale,
+            unit,
+            width = usize::from(decimals)
+        )
+    }
+
+    /// Write a text field as a quoted string constant (`"home"`)
+    ///
+    /// Quoting rules, following modem conventions: text fields are always
+    /// enclosed in double quotes and numeric fields (`field_u32`,
+    /// `field_i32`) never are, so a host can type every field from its
+    /// first character. The convention is chosen per field by the method
+    /// used. Inside the quotes, `"` and `\` are written as the V.250 hex
+    /// escapes `\22` and `\5C`. Separators are left to the caller.
+    pub fn field_str(&mut self, value: &str) -> core::fmt::Result {
+        self.write_char('"')?;
+        for c in value.chars() {
+            match c {
+                '"' => self.write_str("\\22")?,
+                '\\' => self.write_str("\\5C")?,
+                c => self.write_char(c)?,
+            }
+        }
+        self.write_char('"')
+    }
+
+    /// Write an unsigned numeric field, bare (see `field_str`)
+    pub fn field_u32(&mut self, value: u32) -> core::fmt::Result {
+        write!(self, "{}", value)
+    }
+
+    /// Write a signed numeric field, bare (see `field_str`)
+    pub fn field_i32(&mut self, value: i32) -> core::fmt::Result {
+        write!(self, "{}", value)
+    }
+
+    /// Write `value` as a fixed-width field, e.g. for aligned `AT&V`-style
+    /// dumps: `("57600", 8, ' ', Align::Right)` writes `   57600`
+    ///
+    /// The width counts characters, not bytes. A value longer than `width`
+    /// is truncated to its first `width` characters, whatever the
+    /// alignment, so the columns that follow stay aligned.
+    pub fn field_padded(&mut self, value: impl core::fmt::Display, width: usize, pad: char, align: Align) -> core::fmt::Result {
+        let mut count = CharCount(0);
+        write!(count, "{}", value)?;
+        let padding = width.saturating_sub(count.0);
+
+        if align == Align::Right {
+            (0..padding).try_for_each(|_| self.write_char(pad))?;
+        }
+        write!(Truncate { writer: self, left: width }, "{}", value)?;
+        if align == Align::Left {
+            (0..padding).try_for_each(|_| self.write_char(pad))?;
+        }
+        Ok(())
+    }
+
+    /// Split the written text into chunks of at most `mtu` bytes
+    /// See `chunks` for the splitting rules
+    pub fn chunks(&self, mtu: usize) -> Chunks<'_> {
+        chunks(self.as_str(), mtu)
+    }
+
+    /// Start a count-prefixed list (`3,a,b,c`) by writing `count`
+    ///
+    /// Each `CountedList::field` then writes `,value`. `CountedList::finish`
+    /// checks that exactly `count` fields were written with a debug
+    /// assertion: a mismatch panics in debug builds, while release builds
+    /// skip the check and keep the text as written.
+    ///
+    /// ```ignore
+    /// let mut list = w.counted_list(rows.len())?;
+    /// for row in rows {
+    ///     list.field(row)?;
+    /// }
+    /// list.finish()
+    /// ```
+    pub fn counted_list(&mut self, count: usize) -> Result<CountedList<'_, 'b>, core::fmt::Error> {
+        write!(self, "{}", count)?;
+        Ok(CountedList { writer: self, declared: count, written: 0 })
+    }
+}
+
+/// Count-prefixed list in progress (see `ResponseWriter::counted_list`)
+pub struct CountedList<'w, 'b> {
+    /// Writer the list is written to
+    writer: &'w mut ResponseWriter<'b>,
+    /// Number of fields announced by the count prefix
+    declared: usize,
+    /// Number of fields written so far
+    written: usize,
+}
+
+impl CountedList<'_, '_> {
+
+    /// Write the next field, preceded by its `,` separator
+    pub fn field(&mut self, value: impl core::fmt::Display) -> core::fmt::Result {
+        write!(self.writer, ",{}", value)?;
+        self.written += 1;
+        Ok(())
+    }
+
+    /// End the list, checking the declared count in debug builds
+    ///
+    /// # Panics
+    /// In debug builds, when the number of fields written differs from the
+    /// count given to `counted_list`.
+    pub fn finish(self) -> core::fmt::Result {
+        debug_assert_eq!(self.written, self.declared, "counted list declared {} fields but {} were written", self.declared, self.written);
+        Ok(())
+    }
+}
+
+/// Sink counting the characters written to it
+struct CharCount(usize);
+
+impl Write for CharCount {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.0 += s.chars().count();
+        Ok(())
+    }
+}
+
+/// Writer adapter dropping everything past its first `left` characters
+struct Truncate<'w, 'b> {
+    /// Destination writer
+    writer: &'w mut ResponseWriter<'b>,
+    /// Number of characters still accepted
+    left: usize,
+}
+
+impl Write for Truncate<'_, '_> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        let end = s.char_indices().nth(self.left).map_or(s.len(), |(i, _)| i);
+        self.left -= s[..end].chars().count();
+        self.writer.write_str(&s[..end])
+    }
+}
+
+impl<'b> Write for ResponseWriter<'b> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.write_bytes(s.as_bytes())
+    }
+}
+
+/// Split a framed response into chunks of at most `mtu` bytes
+/// (e.g. one BLE notification each)
+///
+/// Each chunk ends right after the last field separator (`,`) or line end
+/// (`\n`) that fits, so fields and lines are kept whole when possible; a
+/// field longer than `mtu` is split hard at the MTU boundary, never inside
+/// a UTF-8 character. The framing terminator (`\r\nOK\r\n`) is part of the
+/// text like any other line, so it ends up in the last chunk(s) and may be
+/// split across two chunks when it does not fit. An `mtu` of 0 is treated
+/// as 1, and a chunk grows past `mtu` only to hold a single character wider
+/// than the MTU.
+pub fn chunks(text: &str, mtu: usize) -> Chunks<'_> {
+    Chunks { rest: text, mtu: mtu.max(1) }
+}
+
+/// Write `template` with its `{name}` placeholders replaced by `values`
+///
+/// Substitution is minimal: `{name}` is replaced by the `Display` output
+/// of the value registered under `name`, and `{{` / `}}` write a literal
+/// brace. There is no format specification (width, precision...). An
+/// unknown name or an unterminated `{` is written verbatim, so a help text
+/// never fails to render:
+///
+/// ```ignore
+/// write_template(out, "brightness: 0-{max}", &[("max", &self.max)])?;
+/// ```
+pub fn write_template(out: &mut dyn Write, template: &str, values: &[(&str, &dyn core::fmt::Display)]) -> core::fmt::Result {
+    let mut rest = template;
+    while let Some(pos) = rest.find(['{', '}']) {
+        out.write_str(&rest[..pos])?;
+        let tail = &rest[pos..];
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            out.write_str(&tail[..1])?;
+            rest = &tail[2..];
+            continue;
+        }
+        let placeholder = tail.strip_prefix('{').and_then(|t| t.split_once('}'));
+        match placeholder.and_then(|(name, after)| values.iter().find(|(n, _)| *n == name).map(|(_, v)| (v, after))) {
+            Some((value, after)) => {
+                write!(out, "{}", value)?;
+                rest = after;
+            }
+            None => {
+                out.write_str(&tail[..1])?;
+                rest = &tail[1..];
+            }
+        }
+    }
+    out.write_str(rest)
+}
+
+/// Iterator over MTU-bounded response chunks (see `chunks`)
+pub struct Chunks<'r> {
+    /// Text not yielded yet
+    rest: &'r str,
+    /// Maximum chunk size in bytes
+    mtu: usize,
+}
+
+impl<'r> Iterator for Chunks<'r> {
+    type Item = &'r str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rest.is_empty() {
+            return None;
+        }
+        if self.rest.len() <= self.mtu {
+            return Some(core::mem::take(&mut self.rest));
+        }
+
+        // Prefer cutting right after a separator within the MTU window
+        let window = &self.rest.as_bytes()[..self.mtu];
+        let mut end = match window.iter().rposition(|b| *b == b',' || *b == b'\n') {
+            Some(pos) => pos + 1,
+            None => self.mtu,
+        };
+
+        // Hard split: move back to a character boundary, or forward if the
+        // first character alone is wider than the MTU
+        while !self.rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        if end == 0 {
+            end = self.rest.chars().next().map_or(1, char::len_utf8);
+        }
+
+        let (chunk, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        Some(chunk)
+    }
+}
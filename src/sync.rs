@@ -0,0 +1,77 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+//! Thread-safe parser wrapper for RTOS targets (requires the `osal_rs` feature)
+//!
+//! `SyncAtParser` guards an `AtParser` behind the `osal_rs` mutex so several
+//! tasks (e.g. a UART task and a management task) can share it.
+//!
+//! Locking granularity: the mutex is held for the whole `execute` call,
+//! including the command handler, so commands never run concurrently.
+//!
+//! Deadlocks: a handler must never call back into the same `SyncAtParser`
+//! (directly or by waiting on a task that does), since the lock is not
+//! recursive. Keep handlers short to limit the time other tasks are blocked.
+
+use core::fmt::Write;
+
+use osal_rs::sync::Mutex;
+
+use crate::context::AtContext;
+use crate::parser::AtParser;
+use crate::AtResult;
+
+/// `AtParser` wrapper sharing a single parser between tasks
+pub struct SyncAtParser<'a, T> {
+    /// The guarded parser
+    inner: Mutex<AtParser<'a, T>>,
+}
+
+/// Compile-time check: the wrapper over `Send` modules can be shared between
+/// tasks
+const _: () = {
+    const fn assert_send_sync<S: Send + Sync>() {}
+    #[allow(dead_code)]
+    const fn wrapper_is_shareable<'a, T: Send + 'a>() {
+        assert_send_sync::<SyncAtParser<'a, T>>();
+    }
+};
+
+impl<'a, T> SyncAtParser<'a, T>
+where
+    T: AtContext + Send {
+
+    /// Wrap a configured parser
+    pub fn new(parser: AtParser<'a, T>) -> Self {
+        Self { inner: Mutex::new(parser) }
+    }
+
+    /// Parse and execute an AT command string under the parser lock
+    /// See `AtParser::execute`
+    pub fn execute(&self, input: &str) -> AtResult<'static> {
+        self.inner.lock().execute(input)
+    }
+
+    /// Parse and execute an AT command string under the parser lock,
+    /// writing the framed response (see `AtParser::execute_framed`)
+    pub fn execute_framed(&self, input: &str, out: &mut dyn Write) -> core::fmt::Result {
+        self.inner.lock().execute_framed(input, out)
+    }
+}
@@ -0,0 +1,96 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+ 
+use core::mem::MaybeUninit;
+
+use crate::AtError;
+
+/// Fixed-capacity command table filled at runtime
+///
+/// Useful when the command set is assembled conditionally at startup:
+/// entries are appended with `push` or `extend_commands` and the table is
+/// then handed to `AtParser::set_commands` through `as_mut_slice`.
+///
+/// The capacity `N` is fixed at compile time and the table never allocates.
+/// Entries keep their insertion order, which is also the lookup order.
+pub struct CommandTable<'a, T: ?Sized, const N: usize> {
+    /// Storage, only the first `len` entries are initialized
+    entries: [MaybeUninit<(&'a str, &'a mut T)>; N],
+    /// Number of initialized entries
+    len: usize,
+}
+
+impl<'a, T: ?Sized, const N: usize> CommandTable<'a, T, N> {
+
+    /// Create an empty table
+    pub const fn new() -> Self {
+        Self { entries: [const { MaybeUninit::uninit() }; N], len: 0 }
+    }
+
+    /// Append a command
+    /// Returns `AtError::BufferFull` when the table already holds `N` entries
+    pub fn push(&mut self, name: &'a str, module: &'a mut T) -> Result<(), AtError> {
+        let slot = self.entries.get_mut(self.len).ok_or(AtError::BufferFull)?;
+        slot.write((name, module));
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Append every command produced by `commands`
+    /// Stops with `AtError::BufferFull` at the first entry that does not fit;
+    /// entries appended before that point are kept.
+    pub fn extend_commands<I>(&mut self, commands: I) -> Result<(), AtError>
+    where
+        I: IntoIterator<Item = (&'a str, &'a mut T)> {
+        for (name, module) in commands {
+            self.push(name, module)?;
+        }
+        Ok(())
+    }
+
+    /// Number of registered commands
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if no command is registered
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Maximum number of commands (`N`)
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Get the registered commands, ready for `AtParser::set_commands`
+    pub fn as_mut_slice(&mut self) -> &mut [(&'a str, &'a mut T)] {
+        // SAFETY: the first `len` entries have been initialized by `push`,
+        // and `MaybeUninit<X>` has the same layout as `X`
+        unsafe { core::slice::from_raw_parts_mut(self.entries.as_mut_ptr().cast(), self.len) }
+    }
+}
+
+impl<'a, T: ?Sized, const N: usize> Default for CommandTable<'a, T, N> {
+
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,83 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+
+//! Unsolicited result codes (URCs)
+//!
+//! URCs are lines the device sends on its own, outside of any command
+//! response (e.g. `+RING: 1` or `+WIFI: DISCONNECTED`). They use the same
+//! `+NAME: value` shape as prefixed responses (see
+//! `AtParser::set_response_prefix`).
+//!
+//! # Timestamps
+//!
+//! When a clock is attached, the monotonic time is inserted as the first
+//! field, in the clock's unit (milliseconds by convention):
+//! `+NAME: <time>,value\r\n`, or `+NAME: <time>\r\n` without payload. The
+//! clock is a plain function so tests can inject a fake one; on an RTOS it
+//! typically wraps the `osal_rs` tick counter converted to milliseconds.
+
+use core::fmt::Write;
+
+use crate::command_suffix;
+
+/// Monotonic clock source used to timestamp URCs
+pub type Clock = fn() -> u64;
+
+/// URC emitter, optionally timestamping every line
+#[derive(Default)]
+pub struct UrcWriter {
+    /// Source of the timestamps, `None` for plain URCs
+    clock: Option<Clock>,
+}
+
+impl UrcWriter {
+
+    /// Create an emitter without timestamps
+    pub const fn new() -> Self {
+        Self { clock: None }
+    }
+
+    /// Attach a clock to timestamp every URC; `None` disables timestamps
+    pub fn set_clock(&mut self, clock: Option<Clock>) {
+        self.clock = clock;
+    }
+
+    /// Write one URC line
+    ///
+    /// # Arguments
+    /// * `out` - Destination (usually the UART)
+    /// * `name` - URC name, with or without its `AT+` prefix (e.g. "RING");
+    ///   a name starting with `AT` needs the prefix (`"AT+ATTACH"`), see
+    ///   `command_suffix`
+    /// * `payload` - URC value, empty for a bare `+NAME` line
+    ///
+    /// # Returns
+    /// * `Ok(())` - The line was written
+    /// * `Err(fmt::Error)` - The writer failed
+    pub fn emit(&self, out: &mut dyn Write, name: &str, payload: &str) -> core::fmt::Result {
+        let name = command_suffix(name);
+        match (self.clock, payload.is_empty()) {
+            (Some(clock), true) => write!(out, "+{}: {}\r\n", name, clock()),
+            (Some(clock), false) => write!(out, "+{}: {},{}\r\n", name, clock(), payload),
+            (None, true) => write!(out, "+{}\r\n", name),
+            (None, false) => write!(out, "+{}: {}\r\n", name, payload),
+        }
+    }
+}
@@ -0,0 +1,265 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+
+//! Integration tests for argument access helpers
+
+use at_parser_rs::parser::{parse, AtForm};
+use at_parser_rs::{percent_decode, Args, AtError};
+
+#[test]
+fn numeric_getters_accept_modem_style_integers() {
+    let args = Args { raw: "007,+5,-0,-12" };
+    assert_eq!(args.get_u32(0), Ok(7));
+    assert_eq!(args.get_i32(1), Ok(5));
+    assert_eq!(args.get_u32(1), Ok(5));
+    assert_eq!(args.get_i32(2), Ok(0));
+    assert_eq!(args.get_u32(2), Ok(0));
+    assert_eq!(args.get_i32(3), Ok(-12));
+}
+
+#[test]
+fn numeric_getters_reject_invalid_values() {
+    let args = Args { raw: "4294967296,2147483648,-1,+,1a, 1" };
+    assert_eq!(args.get_u32(0), Err(AtError::InvalidArgs));
+    assert_eq!(args.get_i32(1), Err(AtError::InvalidArgs));
+    assert_eq!(args.get_u32(2), Err(AtError::InvalidArgs));
+    assert_eq!(args.get_i32(3), Err(AtError::InvalidArgs));
+    assert_eq!(args.get_i32(4), Err(AtError::InvalidArgs));
+    assert_eq!(args.get_i32(5), Err(AtError::InvalidArgs));
+    assert_eq!(args.get_u32(6), Err(AtError::MissingArg));
+}
+
+#[test]
+fn numeric_getters_do_not_panic_on_huge_values() {
+    let args = Args { raw: "99999999999999999999999999,-99999999999999999999999999" };
+    assert_eq!(args.get_u32(0), Err(AtError::InvalidArgs));
+    assert_eq!(args.get_i32(1), Err(AtError::InvalidArgs));
+}
+
+#[test]
+fn range_getter_checks_inclusive_bounds() {
+    let args = Args { raw: "0,100,101" };
+    assert_eq!(args.get_u32_in(0, 0, 100), Ok(0));
+    assert_eq!(args.get_u32_in(1, 0, 100), Ok(100));
+    assert_eq!(args.get_u32_in(2, 0, 100), Err(AtError::InvalidArgs));
+    assert_eq!(args.get_u32_in(1, 100, 0), Err(AtError::InvalidArgs));
+}
+
+#[test]
+fn matches_ignores_ascii_case() {
+    let args = Args { raw: "ON,off" };
+    assert!(args.matches(0, "on"));
+    assert!(args.matches(1, "OFF"));
+    assert!(!args.matches(0, "off"));
+    assert!(!args.matches(2, ""));
+}
+
+#[test]
+fn pair_and_triple_require_the_exact_field_count() {
+    assert_eq!(Args { raw: "10,20" }.get_pair_u32(), Ok((10, 20)));
+    assert_eq!(Args { raw: "10" }.get_pair_u32(), Err(AtError::InvalidArgs));
+    assert_eq!(Args { raw: "10,20,30" }.get_pair_u32(), Err(AtError::InvalidArgs));
+    assert_eq!(Args { raw: "10," }.get_pair_u32(), Err(AtError::InvalidArgs));
+
+    assert_eq!(Args { raw: "255,128,0" }.get_triple_u32(), Ok((255, 128, 0)));
+    assert_eq!(Args { raw: "255,128" }.get_triple_u32(), Err(AtError::InvalidArgs));
+}
+
+#[test]
+fn parse_tuple_converts_every_field() {
+    let args = Args { raw: "5,1,1000,name" };
+    assert_eq!(args.parse_tuple::<(u8, bool, u32, &str)>(), Ok((5, true, 1000, "name")));
+    assert_eq!(args.parse_tuple::<(u8, bool, u32)>(), Err(AtError::InvalidArgs));
+    assert_eq!(Args { raw: "300" }.parse_tuple::<(u8,)>(), Err(AtError::InvalidArgs));
+    assert_eq!(Args { raw: "2" }.parse_tuple::<(bool,)>(), Err(AtError::InvalidArgs));
+}
+
+#[test]
+fn fixed_point_arguments_are_scaled_without_rounding() {
+    let args = Args { raw: "23.5,23.555,-0.05,7,1.,.5,+-1,x.1" };
+    assert_eq!(args.get_fixed(0, 100), Ok(2350));
+    assert_eq!(args.get_fixed(1, 100), Err(AtError::InvalidArgs));
+    assert_eq!(args.get_fixed(1, 1000), Ok(23555));
+    assert_eq!(args.get_fixed(2, 100), Ok(-5));
+    assert_eq!(args.get_fixed(3, 1), Ok(7));
+    assert_eq!(args.get_fixed(0, 1), Err(AtError::InvalidArgs));
+    assert_eq!(args.get_fixed(0, 50), Err(AtError::InvalidArgs));
+    for index in 4..8 {
+        assert_eq!(args.get_fixed(index, 100), Err(AtError::InvalidArgs));
+    }
+    assert_eq!(args.get_fixed(8, 100), Err(AtError::MissingArg));
+    assert_eq!(Args { raw: "30000000" }.get_fixed(0, 100), Err(AtError::InvalidArgs));
+}
+
+mod typed_set {
+    use at_parser_rs::context::AtContext;
+    use at_parser_rs::{at_set, AtError};
+
+    struct Led {
+        state: bool,
+        brightness: u8,
+    }
+
+    impl AtContext for Led {
+        at_set!(|self, state: bool, brightness: Option<u8>| {
+            self.state = state;
+            if let Some(brightness) = brightness {
+                self.brightness = brightness;
+            }
+            Ok("")
+        });
+    }
+
+    #[test]
+    fn at_set_parses_typed_parameters() {
+        let mut led = Led { state: false, brightness: 10 };
+        assert_eq!(led.set(super::Args { raw: "1" }), Ok(""));
+        assert!(led.state);
+        assert_eq!(led.brightness, 10);
+
+        assert_eq!(led.set(super::Args { raw: "0,75" }), Ok(""));
+        assert!(!led.state);
+        assert_eq!(led.brightness, 75);
+
+        assert_eq!(led.set(super::Args { raw: "2" }), Err(AtError::InvalidArgs));
+        assert_eq!(led.set(super::Args { raw: "1,300" }), Err(AtError::InvalidArgs));
+    }
+}
+
+#[test]
+fn query_params_are_percent_decoded() {
+    let args = Args { raw: "\"ssid=my%20net&pass=a%2Bb+c&empty=&flag\"" };
+    let mut buf = [0u8; 16];
+    assert_eq!(args.query_param("ssid", &mut buf), Ok(Some("my net")));
+    assert_eq!(args.query_param("pass", &mut buf), Ok(Some("a+b c")));
+    assert_eq!(args.query_param("empty", &mut buf), Ok(Some("")));
+    assert_eq!(args.query_param("flag", &mut buf), Ok(Some("")));
+    assert_eq!(args.query_param("missing", &mut buf), Ok(None));
+    assert_eq!(args.query_param("ssid", &mut buf[..3]), Err(AtError::BufferFull));
+
+    let args = Args { raw: "a=%4&b=%zz&c=%ff" };
+    assert_eq!(args.query_param("a", &mut buf), Err(AtError::InvalidArgs));
+    assert_eq!(args.query_param("b", &mut buf), Err(AtError::InvalidArgs));
+    assert_eq!(args.query_param("c", &mut buf), Err(AtError::InvalidArgs));
+    assert_eq!(percent_decode("caf%C3%A9", &mut buf), Ok("café"));
+}
+
+#[test]
+fn named_fields_report_their_keys() {
+    let args = Args { raw: "1,mode=2,level=5,x" };
+    assert_eq!(args.present_keys().collect::<Vec<_>>(), ["mode", "level"]);
+    assert_eq!(args.named("level"), Some("5"));
+    assert_eq!(args.named("x"), None);
+    assert_eq!(args.named("1"), None);
+    assert_eq!(Args { raw: "" }.present_keys().count(), 0);
+}
+
+#[test]
+fn parse_with_runs_an_external_parser() {
+    fn color(raw: &str) -> Result<(u8, u8, u8), core::num::ParseIntError> {
+        let hex = raw.strip_prefix('#').unwrap_or(raw);
+        let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2).unwrap_or("x"), 16);
+        Ok((channel(0)?, channel(2)?, channel(4)?))
+    }
+
+    assert_eq!(Args { raw: "#ff8000" }.parse_with(color), Ok((255, 128, 0)));
+    assert_eq!(Args { raw: "#ff80" }.parse_with(color), Err(AtError::InvalidArgs));
+    assert_eq!(Args { raw: "a,b" }.parse_with(|raw| Ok::<_, ()>(raw.len())), Ok(3));
+}
+
+#[test]
+fn iter_offsets_yields_field_positions() {
+    let args = Args { raw: "12,,abc,é,x" };
+    assert_eq!(args.iter_offsets().collect::<Vec<_>>(), [(0, "12"), (3, ""), (4, "abc"), (8, "é"), (11, "x")]);
+    assert_eq!(Args { raw: "" }.iter_offsets().collect::<Vec<_>>(), [(0, "")]);
+}
+
+#[test]
+fn ipv4_arguments_are_parsed_into_octets() {
+    let args = Args { raw: "192.168.1.10,\"10.0.0.255\",0.0.0.0" };
+    assert_eq!(args.get_ipv4(0), Ok([192, 168, 1, 10]));
+    assert_eq!(args.get_ipv4(1), Ok([10, 0, 0, 255]));
+    assert_eq!(args.get_ipv4(2), Ok([0, 0, 0, 0]));
+    assert_eq!(args.get_ipv4(3), Err(AtError::MissingArg));
+
+    let args = Args { raw: "1.2.3,1.2.3.4.5,1.2.3.256,1..3.4,+1.2.3.4,1.2.3.0004,1.2.3.4 ,\"1.2.3.4" };
+    for index in 0..8 {
+        assert_eq!(args.get_ipv4(index), Err(AtError::InvalidArgs));
+    }
+}
+
+#[test]
+fn durations_are_normalized_to_milliseconds() {
+    let args = Args { raw: "500,500ms,10s,2m,0s,4294967295" };
+    let expected = [500, 500, 10_000, 120_000, 0, u32::MAX];
+    for (index, ms) in expected.into_iter().enumerate() {
+        assert_eq!(args.get_duration_ms(index), Ok(ms));
+    }
+    assert_eq!(args.get_duration_ms(6), Err(AtError::MissingArg));
+
+    let args = Args { raw: "10h,s,10S,-1s,1.5s,4294968s,10 s,ms10" };
+    for index in 0..8 {
+        assert_eq!(args.get_duration_ms(index), Err(AtError::InvalidArgs));
+    }
+}
+
+#[test]
+fn parse_all_u32_fills_the_caller_buffer() {
+    let mut out = [0u32; 4];
+    assert_eq!(Args { raw: "1,+2,003,4" }.parse_all_u32(&mut out), Ok(4));
+    assert_eq!(out, [1, 2, 3, 4]);
+    assert_eq!(Args { raw: "9" }.parse_all_u32(&mut out), Ok(1));
+    assert_eq!(out[0], 9);
+    assert_eq!(Args { raw: "" }.parse_all_u32(&mut out), Ok(0));
+
+    assert_eq!(Args { raw: "1,2,3,4,5" }.parse_all_u32(&mut out), Err(AtError::InvalidArgs));
+    assert_eq!(Args { raw: "1,,3" }.parse_all_u32(&mut out), Err(AtError::InvalidArgs));
+    assert_eq!(Args { raw: "1,-2" }.parse_all_u32(&mut out), Err(AtError::InvalidArgs));
+}
+
+#[test]
+fn quoted_fields_keep_commas_and_lose_quotes() {
+    let Ok(("AT+CMGS", AtForm::Set(args))) = parse("AT+CMGS=\"+15551234\",145") else {
+        panic!("CMGS line not parsed as a Set");
+    };
+    assert_eq!(args.iter_quoted().collect::<Vec<_>>(), ["+15551234", "145"]);
+    assert_eq!(args.get_quoted(0), Some("+15551234"));
+    assert_eq!(args.get_u32(1), Ok(145));
+
+    let args = Args { raw: "\"Hello, world\",\"\",7,\"open" };
+    assert_eq!(args.iter_quoted().collect::<Vec<_>>(), ["Hello, world", "", "7", "\"open"]);
+    assert_eq!(args.get_quoted(4), None);
+    assert_eq!(Args { raw: "" }.iter_quoted().collect::<Vec<_>>(), [""]);
+}
+
+#[test]
+fn raw_len_counts_payload_bytes_quotes_included() {
+    let Ok(("AT+SEND", AtForm::Set(args))) = parse("AT+SEND=\"a,b\",7") else {
+        panic!("SEND line not parsed as a Set");
+    };
+    // Quotes and separators are part of the raw payload
+    assert_eq!(args.raw_len(), 7);
+    assert_eq!(args.raw_len(), args.raw_payload().len());
+    assert_eq!(args.get_quoted(0).map(str::len), Some(3));
+
+    // Bytes, not characters
+    assert_eq!(Args { raw: "\"é\"" }.raw_len(), 4);
+    assert_eq!(Args { raw: "" }.raw_len(), 0);
+}
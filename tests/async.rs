@@ -0,0 +1,138 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+
+
+//! Integration tests for the async dispatch path
+
+use core::future::Future;
+use core::pin::pin;
+use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+use at_parser_rs::context::AsyncAtContext;
+use at_parser_rs::parser::AtParser;
+use at_parser_rs::{Args, AtError, AtResult};
+
+/// Minimal executor polling a future until it completes
+fn block_on<F: Future>(future: F) -> F::Output {
+    fn noop_raw() -> RawWaker {
+        fn clone(_: *const ()) -> RawWaker {
+            noop_raw()
+        }
+        fn noop(_: *const ()) {}
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+        RawWaker::new(core::ptr::null(), &VTABLE)
+    }
+
+    // SAFETY: the vtable functions do nothing and ignore the data pointer
+    let waker = unsafe { Waker::from_raw(noop_raw()) };
+    let mut cx = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+/// Async LED taking `<state>,<brightness>`, writable only when connected
+#[derive(Default)]
+struct Led {
+    sets: u32,
+}
+
+impl AsyncAtContext for Led {
+    async fn query(&mut self) -> AtResult<'static> {
+        Ok("1,100")
+    }
+
+    async fn set(&mut self, args: Args<'_>) -> AtResult<'static> {
+        args.get(0).ok_or(AtError::InvalidArgs)?;
+        self.sets += 1;
+        Ok("")
+    }
+
+    fn arity(&self) -> Option<usize> {
+        Some(2)
+    }
+
+    fn state_mask(&self) -> u32 {
+        1 << 1
+    }
+}
+
+/// Async constant that can only be read
+struct Version;
+
+impl AsyncAtContext for Version {
+    async fn query(&mut self) -> AtResult<'static> {
+        Ok("1.0")
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+}
+
+#[test]
+fn async_dispatch_applies_the_parser_checks() {
+    let mut led = Led::default();
+    {
+        let commands: &mut [(&str, &mut Led)] = &mut [("AT+LED", &mut led)];
+        let mut parser = AtParser::new();
+        parser.set_commands(commands);
+
+        assert_eq!(block_on(parser.execute_async("AT+LED=1")), Err(AtError::NotSupported));
+        parser.set_state(1);
+        assert_eq!(block_on(parser.execute_async("AT+LED=1")), Ok(""));
+        assert_eq!(block_on(parser.execute_async("AT+LED?")), Ok("1,100"));
+
+        assert_eq!(block_on(parser.execute_async("AT+LED=1,50,99")), Ok(""));
+        parser.set_strict_args(true);
+        assert_eq!(block_on(parser.execute_async("AT+LED=1,50,99")), Err(AtError::InvalidArgs));
+        assert_eq!(block_on(parser.execute_async("AT+LED=1,50")), Ok(""));
+    }
+    assert_eq!(led.sets, 3);
+
+    let mut version = Version;
+    let commands: &mut [(&str, &mut Version)] = &mut [("AT+VER", &mut version)];
+    let mut parser = AtParser::new();
+    parser.set_commands(commands);
+    assert_eq!(block_on(parser.execute_async("AT+VER?")), Ok("1.0"));
+    assert_eq!(block_on(parser.execute_async("AT+VER=2.0")), Err(AtError::ReadOnly));
+}
+
+#[test]
+fn async_commands_outside_their_states_never_run() {
+    let mut led = Led::default();
+    {
+        let commands: &mut [(&str, &mut Led)] = &mut [("AT+LED", &mut led)];
+        let mut parser = AtParser::new();
+        parser.set_commands(commands);
+
+        for state in [0, 2, 31] {
+            parser.set_state(state);
+            assert_eq!(block_on(parser.execute_async("AT+LED=1")), Err(AtError::NotSupported));
+            assert_eq!(block_on(parser.execute_async("AT+LED?")), Err(AtError::NotSupported));
+        }
+        parser.set_state(1);
+        assert_eq!(block_on(parser.execute_async("AT+LED=1")), Ok(""));
+    }
+    assert_eq!(led.sets, 1);
+}
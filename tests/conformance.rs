@@ -0,0 +1,195 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+
+//! Corpus of malformed AT lines
+//! Conformance suite against a representative modem command set
+//!
+//! Drives the full parser through the public API only, with the commands
+//! found on most modems, and checks the framing (`payload\r\nOK\r\n`,
+//! `ERROR\r\n`, `+CME ERROR: <code>\r\n`) and the reported error codes.
+
+use core::fmt::Write;
+
+use at_parser_rs::context::AtContext;
+use at_parser_rs::parser::AtParser;
+use at_parser_rs::{Args, AtError, AtResult};
+
+/// Simulated device state shared by every command
+struct Modem {
+    echo: bool,
+    baud: u32,
+    parity: u32,
+    stop_bits: u32,
+}
+
+impl Default for Modem {
+    fn default() -> Self {
+        Self { echo: true, baud: 115200, parity: 0, stop_bits: 1 }
+    }
+}
+
+/// One command of the simulated modem, dispatching on its kind
+enum Command {
+    /// `ATE0` / `ATE1`, registered as `ATE#`
+    Echo(Modem),
+    /// `AT&F`
+    FactoryReset(Modem),
+    /// `ATI`
+    Info,
+    /// `AT+UART=<baud>,<parity>,<stop bits>`
+    Uart(Modem),
+}
+
+impl Command {
+    fn modem(&mut self) -> Option<&mut Modem> {
+        match self {
+            Command::Echo(m) | Command::FactoryReset(m) | Command::Uart(m) => Some(m),
+            Command::Info => None,
+        }
+    }
+}
+
+impl AtContext for Command {
+    fn exec(&self) -> AtResult<'static> {
+        match self {
+            Command::Info => Ok("AT-PARSER-RS 1.0"),
+            Command::FactoryReset(_) => Ok(""),
+            _ => Err(AtError::NotSupported),
+        }
+    }
+
+    fn query(&mut self) -> AtResult<'static> {
+        match self {
+            Command::Uart(m) if m.baud == 9600 => Ok("+UART: 9600,1,2"),
+            Command::Uart(_) => Ok("+UART: 115200,0,1"),
+            _ => Err(AtError::NotSupported),
+        }
+    }
+
+    fn test(&mut self) -> AtResult<'static> {
+        match self {
+            Command::Uart(_) => Ok("+UART: (9600,115200),(0-2),(1-2)"),
+            _ => Err(AtError::NotSupported),
+        }
+    }
+
+    fn validate_set(&self, args: Args) -> Result<(), AtError> {
+        if let Command::Uart(_) = self {
+            let (baud, parity, stop_bits) = args.get_triple_u32()?;
+            if !matches!(baud, 9600 | 115200) || parity > 2 || !(1..=2).contains(&stop_bits) {
+                return Err(AtError::InvalidArgs);
+            }
+        }
+        Ok(())
+    }
+
+    fn set(&mut self, args: Args) -> AtResult<'static> {
+        let Some(m) = self.modem() else {
+            return Err(AtError::NotSupported);
+        };
+        (m.baud, m.parity, m.stop_bits) = args.get_triple_u32()?;
+        Ok("")
+    }
+
+    fn exec_indexed(&mut self, index: u32) -> AtResult<'static> {
+        match (self, index) {
+            (Command::Echo(m), 0 | 1) => {
+                m.echo = index == 1;
+                Ok("")
+            }
+            _ => Err(AtError::InvalidArgs),
+        }
+    }
+}
+
+fn cme_error(error: AtError, out: &mut dyn Write) -> core::fmt::Result {
+    write!(out, "+CME ERROR: {}\r\n", error.code())
+}
+
+/// Run every input line through a fresh modem and collect the framed output
+fn session(inputs: &[&str], numeric_errors: bool) -> (String, [Command; 4]) {
+    let mut echo = Command::Echo(Modem::default());
+    let mut reset = Command::FactoryReset(Modem::default());
+    let mut info = Command::Info;
+    let mut uart = Command::Uart(Modem::default());
+    let mut out = String::new();
+    {
+        let commands: &mut [(&str, &mut Command)] = &mut [
+            ("ATE#", &mut echo),
+            ("AT&F", &mut reset),
+            ("ATI", &mut info),
+            ("AT+UART", &mut uart),
+        ];
+        let mut parser = AtParser::new();
+        parser.set_commands(commands);
+        if numeric_errors {
+            parser.set_error_formatter(Some(cme_error));
+        }
+        for input in inputs {
+            parser.execute_framed(input, &mut out).unwrap();
+        }
+    }
+    (out, [echo, reset, info, uart])
+}
+
+#[test]
+fn echo_control() {
+    let (out, [echo, ..]) = session(&["ATE0"], false);
+    assert_eq!(out, "OK\r\n");
+    let Command::Echo(m) = echo else { unreachable!() };
+    assert!(!m.echo);
+
+    let (out, _) = session(&["ATE1", "ATE2", "ATE"], false);
+    assert_eq!(out, "OK\r\nERROR\r\nERROR\r\n");
+}
+
+#[test]
+fn factory_reset_and_info() {
+    let (out, _) = session(&["AT&F", "ATI", "ATI?"], false);
+    assert_eq!(out, "OK\r\nAT-PARSER-RS 1.0\r\nOK\r\nERROR\r\n");
+}
+
+#[test]
+fn multi_argument_configuration() {
+    let (out, [.., uart]) = session(&["AT+UART=?", "AT+UART=9600,1,2", "AT+UART?"], false);
+    assert_eq!(
+        out,
+        "+UART: (9600,115200),(0-2),(1-2)\r\nOK\r\nOK\r\n+UART: 9600,1,2\r\nOK\r\n"
+    );
+    let Command::Uart(m) = uart else { unreachable!() };
+    assert_eq!((m.baud, m.parity, m.stop_bits), (9600, 1, 2));
+}
+
+#[test]
+fn rejected_configuration_leaves_state_untouched() {
+    let (out, [.., uart]) = session(&["AT+UART=1200,0,1", "AT+UART=9600,1", "AT+UART=9600,x,1"], true);
+    assert_eq!(out, "+CME ERROR: 3\r\n+CME ERROR: 3\r\n+CME ERROR: 3\r\n");
+    let Command::Uart(m) = uart else { unreachable!() };
+    assert_eq!(m.baud, 115200);
+}
+
+#[test]
+fn error_codes() {
+    let (out, _) = session(&["AT+NOPE", "ATI=1", "=1", "AT+UART=9600,0,1,1"], true);
+    assert_eq!(
+        out,
+        "+CME ERROR: 1\r\n+CME ERROR: 2\r\n+CME ERROR: 8\r\n+CME ERROR: 3\r\n"
+    );
+}
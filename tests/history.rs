@@ -0,0 +1,92 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+
+
+//! Integration tests for the command execution log
+
+use at_parser_rs::context::AtContext;
+use at_parser_rs::history::{CommandHistory, History, HistoryEntry};
+use at_parser_rs::parser::AtParser;
+use at_parser_rs::{Args, AtError, AtResult};
+
+/// Module accepting a numeric Set
+struct Probe;
+
+impl AtContext for Probe {
+    fn exec(&self) -> AtResult<'static> {
+        Ok("")
+    }
+
+    fn set(&mut self, args: Args) -> AtResult<'static> {
+        args.get_u32(0)?;
+        Ok("")
+    }
+}
+
+#[test]
+fn ring_keeps_the_last_commands_oldest_first() {
+    let mut history: CommandHistory<3> = CommandHistory::new();
+    for code in 1..=5 {
+        history.record(HistoryEntry { name: None, code });
+    }
+    assert_eq!(history.len(), 3);
+    let codes: Vec<u16> = (0..history.len()).filter_map(|i| history.get(i)).map(|e| e.code).collect();
+    assert_eq!(codes, [3, 4, 5]);
+    assert_eq!(history.get(3), None);
+
+    history.clear();
+    assert!(history.is_empty());
+
+    let mut none: CommandHistory<0> = CommandHistory::new();
+    none.record(HistoryEntry { name: None, code: 1 });
+    assert!(none.is_empty());
+}
+
+#[test]
+fn parser_records_results_and_skips_ignored_lines() {
+    let (mut a, mut b) = (Probe, Probe);
+    let mut history: CommandHistory<4> = CommandHistory::new();
+    let commands: &mut [(&str, &mut Probe)] = &mut [("AT+A", &mut a), ("AT+B", &mut b)];
+    let mut parser = AtParser::new();
+    parser.set_commands(commands);
+    assert_eq!(parser.recent().count(), 0);
+    parser.set_history(Some(&mut history));
+
+    parser.execute("AT+A").unwrap();
+    // Ignored empty lines are not commands and leave no entry
+    parser.execute("").unwrap();
+    parser.execute("  ").unwrap();
+    // Failed commands are kept with their result code
+    assert_eq!(parser.execute("AT+B=x"), Err(AtError::InvalidArgs));
+    assert_eq!(parser.execute("AT+NOPE"), Err(AtError::UnknownCommand));
+    parser.execute("AT+B=1").unwrap();
+    parser.execute("AT+A").unwrap();
+
+    let recent: Vec<HistoryEntry> = parser.recent().collect();
+    assert_eq!(recent, [
+        HistoryEntry { name: Some("AT+B"), code: AtError::InvalidArgs.code() },
+        HistoryEntry { name: None, code: AtError::UnknownCommand.code() },
+        HistoryEntry { name: Some("AT+B"), code: 0 },
+        HistoryEntry { name: Some("AT+A"), code: 0 },
+    ]);
+
+    parser.set_history(None);
+    assert_eq!(parser.recent().count(), 0);
+}
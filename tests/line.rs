@@ -0,0 +1,92 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+
+//! Integration tests for the incremental LineReader
+
+use at_parser_rs::line::LineReader;
+use at_parser_rs::AtError;
+
+/// Feed `bytes` and collect every completed line
+fn lines<const N: usize>(reader: &mut LineReader<N>, bytes: &[u8]) -> Vec<Result<String, AtError>> {
+    bytes
+        .iter()
+        .filter_map(|&b| reader.push(b).map(|line| line.map(str::to_owned)))
+        .collect()
+}
+
+#[test]
+fn crlf_lines_are_split_and_empty_lines_skipped() {
+    let mut reader = LineReader::<32>::new();
+    assert_eq!(
+        lines(&mut reader, b"AT+A?\r\n\r\nAT+B=1\n"),
+        vec![Ok("AT+A?".to_owned()), Ok("AT+B=1".to_owned())]
+    );
+}
+
+#[test]
+fn trailing_backslash_joins_the_next_line() {
+    let mut reader = LineReader::<32>::new();
+    assert_eq!(
+        lines(&mut reader, b"AT+CFG=1,2,\\\r\n3,4\r\n"),
+        vec![Ok("AT+CFG=1,2,3,4".to_owned())]
+    );
+    assert_eq!(lines(&mut reader, b"AT+A=\\\n\\\nx\n"), vec![Ok("AT+A=x".to_owned())]);
+}
+
+#[test]
+fn blank_line_after_a_continuation_completes_the_line() {
+    let mut reader = LineReader::<32>::new();
+    assert_eq!(
+        lines(&mut reader, b"AT+CFG=1,\\\r\n\r\nAT+B\r\n"),
+        vec![Ok("AT+CFG=1,".to_owned()), Ok("AT+B".to_owned())]
+    );
+    assert_eq!(
+        lines(&mut reader, b"AT+CFG=2,\\\n\nAT+B\n"),
+        vec![Ok("AT+CFG=2,".to_owned()), Ok("AT+B".to_owned())]
+    );
+}
+
+#[test]
+fn escaped_backslash_does_not_continue() {
+    let mut reader = LineReader::<32>::new();
+    assert_eq!(
+        lines(&mut reader, b"AT+P=a\\\\\r\nAT+B\r\n"),
+        vec![Ok("AT+P=a\\\\".to_owned()), Ok("AT+B".to_owned())]
+    );
+}
+
+#[test]
+fn overflow_and_invalid_utf8_are_reported() {
+    let mut reader = LineReader::<4>::new();
+    assert_eq!(
+        lines(&mut reader, b"AT+LONG\r\nAT\r\n\xff\r\n"),
+        vec![Err(AtError::LineTooLong), Ok("AT".to_owned()), Err(AtError::InvalidEncoding)]
+    );
+}
+
+#[test]
+fn custom_terminators_complete_lines() {
+    let mut reader = LineReader::<32>::new();
+    reader.set_terminators(&[';', '\x04', 'é']);
+    assert_eq!(
+        lines(&mut reader, b"AT+A?;AT+B=1\x04\r\nAT+C\xc3\xa9;"),
+        vec![Ok("AT+A?".to_owned()), Ok("AT+B=1".to_owned()), Ok("\r\nAT+C\u{e9}".to_owned())]
+    );
+}
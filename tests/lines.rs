@@ -0,0 +1,67 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+
+//! Integration tests for `AtParser::execute_lines`
+
+use at_parser_rs::context::AtContext;
+use at_parser_rs::parser::AtParser;
+use at_parser_rs::{AtError, AtResult};
+
+/// Module answering its query with two rows
+struct Scan;
+
+impl AtContext for Scan {
+    fn exec(&self) -> AtResult<'static> {
+        Err(AtError::InvalidArgs)
+    }
+
+    fn query_iter(&mut self) -> impl Iterator<Item = AtResult<'static>> {
+        ["net1", "net2"].into_iter().map(Ok)
+    }
+}
+
+#[test]
+fn execute_lines_splits_payload_and_terminator() {
+    let mut scan = Scan;
+    let commands: &mut [(&str, &mut Scan)] = &mut [("AT+SCAN", &mut scan)];
+    let mut parser = AtParser::new();
+    parser.set_commands(commands);
+    parser.set_response_prefix(true);
+
+    let lines = parser.execute_lines::<12, 4>("AT+SCAN?").unwrap();
+    assert_eq!(lines, ["+SCAN: net1", "+SCAN: net2", "OK"]);
+
+    let lines = parser.execute_lines::<12, 4>("AT+SCAN").unwrap();
+    assert_eq!(lines, ["ERROR"]);
+    assert!(parser.execute_lines::<12, 4>("").unwrap().is_empty());
+}
+
+#[test]
+fn execute_lines_reports_overflow() {
+    let mut scan = Scan;
+    let commands: &mut [(&str, &mut Scan)] = &mut [("AT+SCAN", &mut scan)];
+    let mut parser = AtParser::new();
+    parser.set_commands(commands);
+    parser.set_response_prefix(true);
+
+    assert_eq!(parser.execute_lines::<10, 4>("AT+SCAN?"), Err(AtError::BufferFull));
+    assert_eq!(parser.execute_lines::<11, 2>("AT+SCAN?"), Err(AtError::BufferFull));
+    assert_eq!(parser.execute_lines::<11, 3>("AT+SCAN?").map(|lines| lines.len()), Ok(3));
+}
@@ -0,0 +1,215 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+
+//! Corpus of malformed AT lines
+//!
+//! Every input must produce a `Result` and never panic. The assertions
+//! double as a description of the accepted grammar: the form is chosen from
+//! the line suffix (`=?`, then `?` with or without a preceding `=`), then
+//! from the first `=`; anything else is an Exec of the whole trimmed line.
+
+use at_parser_rs::context::AtContext;
+use at_parser_rs::parser::{parse, AtForm, AtParser, FormKind, ParseErrorReason};
+use at_parser_rs::{Args, AtError, AtResult};
+
+/// Module echoing the first Set argument
+struct Echo;
+
+impl AtContext for Echo {
+    fn exec(&self) -> AtResult<'static> {
+        Ok("")
+    }
+
+    fn query(&mut self) -> AtResult<'static> {
+        Ok("1")
+    }
+
+    fn set(&mut self, args: Args) -> AtResult<'static> {
+        match args.get(0) {
+            Some("") => Ok("empty"),
+            Some(_) => Ok("value"),
+            None => Err(AtError::MissingArg),
+        }
+    }
+}
+
+/// Run `input` through every string entry point of a parser with `AT+A`
+fn run(input: &str) -> AtResult<'static> {
+    let mut a = Echo;
+    let commands: &mut [(&str, &mut Echo)] = &mut [("AT+A", &mut a)];
+    let mut parser = AtParser::new();
+    parser.set_commands(commands);
+
+    let mut out = String::new();
+    parser.execute_framed(input, &mut out).unwrap();
+    parser.execute_line(input, &mut out).unwrap();
+    parser.execute(input)
+}
+
+#[test]
+fn empty_args_route_to_set() {
+    assert_eq!(run("AT+A="), Ok("empty"));
+    assert_eq!(run("AT+A=,"), Ok("empty"));
+}
+
+#[test]
+fn only_separators() {
+    assert_eq!(run(",,,"), Err(AtError::UnknownCommand));
+    assert_eq!(run(";;;"), Err(AtError::UnknownCommand));
+    assert_eq!(run("="), Err(AtError::SyntaxAt(0)));
+    assert_eq!(run("?"), Err(AtError::SyntaxAt(0)));
+    assert_eq!(run("  =?"), Err(AtError::SyntaxAt(0)));
+
+    let Err(err) = parse("=,,") else { panic!("expected a parse error") };
+    assert_eq!(err.form, FormKind::Set);
+    assert_eq!(err.reason, ParseErrorReason::EmptyName);
+    assert_eq!(err.offset, 0);
+}
+
+#[test]
+fn unterminated_quote_is_kept_verbatim() {
+    assert_eq!(run("AT+A=\"abc"), Ok("value"));
+    let (_, form) = parse("AT+A=\"abc,1").unwrap();
+    let AtForm::Set(args) = form else { panic!("expected Set") };
+    assert_eq!(args.get(0), Some("\"abc"));
+    assert_eq!(args.get(1), Some("1"));
+}
+
+#[test]
+fn giant_arg_count_is_rejected() {
+    let input = format!("AT+A={}", ",".repeat(10_000));
+    assert_eq!(run(&input), Err(AtError::InvalidArgs));
+}
+
+#[test]
+fn embedded_nulls_do_not_match_commands() {
+    assert_eq!(run("AT+A\0?"), Err(AtError::UnknownCommand));
+    assert_eq!(run("\0"), Err(AtError::UnknownCommand));
+    assert_eq!(run("AT+A=\0"), Ok("value"));
+}
+
+#[test]
+fn mixed_line_endings() {
+    assert_eq!(run("AT+A?\r\n"), Ok("1"));
+    assert_eq!(run("\r\rAT+A?\n\n"), Ok("1"));
+    assert_eq!(run("AT+A?\r\nAT+A?"), Err(AtError::UnknownCommand));
+    assert_eq!(run("\r\n\r\n"), Ok(""));
+}
+
+#[test]
+fn trailing_question_mark_after_equals() {
+    // `=?` is the Test form, a `?` after arguments makes a parameterized
+    // Test rather than a Set
+    assert_eq!(run("AT+A=?"), Err(AtError::NotSupported));
+    assert_eq!(run("AT+A=1?"), Err(AtError::NotSupported));
+    assert_eq!(run("AT+A=1,?"), Err(AtError::NotSupported));
+    assert!(matches!(parse("AT+A=1,?"), Ok(("AT+A", AtForm::TestWith(Args { raw: "1," })))));
+    assert_eq!(run("AT+A==1?"), Err(AtError::SyntaxAt(5)));
+}
+
+#[test]
+fn invalid_utf8_bytes() {
+    let mut a = Echo;
+    let commands: &mut [(&str, &mut Echo)] = &mut [("AT+A", &mut a)];
+    let mut parser = AtParser::new();
+    parser.set_commands(commands);
+
+    assert_eq!(parser.execute_bytes(b"AT+A=\xff\xfe"), Err(AtError::InvalidEncoding));
+    assert_eq!(parser.execute_bytes(b"AT+A?"), Ok("1"));
+}
+
+#[test]
+fn ascii_only_rejects_high_bit_bytes() {
+    let mut a = Echo;
+    let commands: &mut [(&str, &mut Echo)] = &mut [("AT+A", &mut a)];
+    let mut parser = AtParser::new();
+    parser.set_commands(commands);
+
+    assert_eq!(parser.execute_bytes(b"AT+A=caf\xc3\xa9"), Ok("value"));
+
+    parser.set_ascii_only(true);
+    assert_eq!(parser.execute_bytes(b"AT+A=caf\xc3\xa9"), Err(AtError::InvalidEncoding));
+    assert_eq!(parser.execute_bytes(b"AT+A=\x80"), Err(AtError::InvalidEncoding));
+    assert_eq!(parser.execute("AT\u{a0}+A?"), Err(AtError::InvalidEncoding));
+    assert_eq!(parser.validate("AT+A=caf\u{e9}"), Err(AtError::InvalidEncoding));
+    assert_eq!(parser.validate("AT+A=cafe"), Ok(("AT+A", FormKind::Set)));
+    assert_eq!(parser.execute_bytes(b"AT+A=cafe"), Ok("value"));
+}
+
+#[test]
+fn doubled_and_leading_equals() {
+    let Err(err) = parse("AT+CMD==1") else { panic!("expected a parse error") };
+    assert_eq!(err.reason, ParseErrorReason::DoubledEquals);
+    assert_eq!(err.offset, 7);
+    assert_eq!(run("AT+A==1"), Err(AtError::SyntaxAt(5)));
+
+    let Err(err) = parse("AT+=1") else { panic!("expected a parse error") };
+    assert_eq!(err.reason, ParseErrorReason::EmptyName);
+    assert_eq!(err.offset, 3);
+
+    let Err(err) = parse("AT=1") else { panic!("expected a parse error") };
+    assert_eq!(err.reason, ParseErrorReason::EmptyName);
+    assert_eq!(err.offset, 2);
+
+    // A bare `AT` is still a valid Exec, and later `=` belong to the arguments
+    assert!(matches!(parse("AT"), Ok(("AT", AtForm::Exec))));
+    assert_eq!(run("AT+A=a=b"), Ok("value"));
+}
+
+#[test]
+fn only_the_outer_whitespace_is_trimmed() {
+    assert_eq!(run(" \tAT+A? \r\n"), Ok("1"));
+    assert_eq!(run("AT+A ?"), Err(AtError::UnknownCommand));
+    assert_eq!(run("AT+ A?"), Err(AtError::UnknownCommand));
+    assert_eq!(run("AT+A =1"), Err(AtError::UnknownCommand));
+    assert_eq!(run("AT+A= 1"), Ok("value"));
+    assert!(matches!(parse("  AT+A =1 "), Ok(("AT+A ", AtForm::Set(Args { raw: "1" })))));
+
+    let mut a = Echo;
+    let commands: &mut [(&str, &mut Echo)] = &mut [("AT+MY CMD", &mut a)];
+    let mut parser = AtParser::new();
+    parser.set_commands(commands);
+    assert_eq!(parser.execute("  AT+MY CMD?\r\n"), Ok("1"));
+    assert_eq!(parser.execute("AT+MY  CMD?"), Err(AtError::UnknownCommand));
+    assert_eq!(parser.validate("AT+MY CMD=1"), Ok(("AT+MY CMD", FormKind::Set)));
+}
+
+#[test]
+fn parse_reports_the_form_kind_and_error_details() {
+    let kinds = ["AT+A", "AT+A?", "AT+A=?", "AT+A=1?", "AT+A=1"].map(|input| parse(input).map(|(name, form)| (name, form.kind())));
+    assert_eq!(
+        kinds,
+        [
+            Ok(("AT+A", FormKind::Exec)),
+            Ok(("AT+A", FormKind::Query)),
+            Ok(("AT+A", FormKind::Test)),
+            Ok(("AT+A", FormKind::Test)),
+            Ok(("AT+A", FormKind::Set)),
+        ]
+    );
+
+    let Err(err) = parse("AT+=1") else { panic!("expected a parse error") };
+    assert_eq!(err.to_string(), "Set form with an empty command name at byte 3");
+    assert_eq!(AtError::from(err), AtError::SyntaxAt(3));
+
+    let Err(err) = parse("AT+A==1") else { panic!("expected a parse error") };
+    assert_eq!(err.form, FormKind::Set);
+    assert_eq!(err.to_string(), "doubled '=' at byte 5");
+}
@@ -0,0 +1,106 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+
+//! Allocation audit: every parser path runs under a global allocator that
+//! counts the allocations made by the test thread
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use at_parser_rs::context::AtContext;
+use at_parser_rs::parser::AtParser;
+use at_parser_rs::response::ResponseWriter;
+use at_parser_rs::{Args, AtError, AtResult};
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// System allocator counting the allocations of the current thread
+struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        // SAFETY: forwarded as is to the system allocator
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        // SAFETY: `ptr` was allocated by the system allocator with `layout`
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Run `f` and return the number of allocations it made
+fn allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+/// Module answering every form
+struct Led {
+    on: bool,
+}
+
+impl AtContext for Led {
+    fn exec(&self) -> AtResult<'static> {
+        Ok("")
+    }
+
+    fn query(&mut self) -> AtResult<'static> {
+        Ok(if self.on { "1" } else { "0" })
+    }
+
+    fn test(&mut self) -> AtResult<'static> {
+        Ok("(0,1)")
+    }
+
+    fn set(&mut self, args: Args) -> AtResult<'static> {
+        self.on = args.get_u32_in(0, 0, 1)? == 1;
+        Ok("")
+    }
+}
+
+#[test]
+fn parser_paths_never_allocate() {
+    let mut led = Led { on: false };
+    let commands: &mut [(&str, &mut Led)] = &mut [("AT+LED", &mut led)];
+    let mut parser = AtParser::new();
+    let mut buf = [0u8; 128];
+
+    let count = allocations(|| {
+        parser.set_commands(commands);
+        assert_eq!(parser.execute("AT+LED=1"), Ok(""));
+        assert_eq!(parser.execute("AT+LED?"), Ok("1"));
+        assert_eq!(parser.execute("AT+LED=2"), Err(AtError::InvalidArgs));
+        assert_eq!(parser.execute_bytes(b"AT+NOPE"), Err(AtError::UnknownCommand));
+        assert_eq!(parser.execute_into("AT+LED=?", &mut buf), Ok(11));
+
+        let mut out = ResponseWriter::new(&mut buf);
+        parser.execute_line("AT+LED=0;AT+LED?;AT+LED==1", &mut out).unwrap();
+        assert_eq!(out.as_str(), "0\r\nERROR\r\n");
+    });
+    assert_eq!(count, 0);
+}
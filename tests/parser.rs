@@ -0,0 +1,1817 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+
+//! Integration tests for the AtParser public API
+
+use at_parser_rs::command::{AtCommand, AtCommandParser};
+use at_parser_rs::context::AtContext;
+use at_parser_rs::parser::{dispatch, measure_response, AtForm, AtParser, FormKind, ParserSettings};
+use at_parser_rs::{Args, AtError, AtResult, ConfigError};
+
+/// Module recording how many times each form was dispatched
+#[derive(Default)]
+struct Probe {
+    sets: u32,
+}
+
+impl AtContext for Probe {
+    fn exec(&self) -> AtResult<'static> {
+        Ok("")
+    }
+
+    fn query(&mut self) -> AtResult<'static> {
+        Ok("1")
+    }
+
+    fn set(&mut self, args: Args) -> AtResult<'static> {
+        self.sets += 1;
+        args.get(0).ok_or(AtError::InvalidArgs)?;
+        Ok("")
+    }
+}
+
+#[test]
+fn execute_line_skips_empty_segments() {
+    let mut a = Probe::default();
+    let mut b = Probe::default();
+    let commands: &mut [(&str, &mut Probe)] = &mut [("AT+A", &mut a), ("AT+B", &mut b)];
+    let mut parser = AtParser::new();
+    parser.set_commands(commands);
+
+    let mut out = String::new();
+    parser.execute_line("AT+A;;AT+B;", &mut out).unwrap();
+    assert_eq!(out, "OK\r\n");
+
+    let mut out = String::new();
+    parser.execute_line(";AT+A?;;AT+B?;", &mut out).unwrap();
+    assert_eq!(out, "1\r\n1\r\nOK\r\n");
+}
+
+#[test]
+fn execute_line_stops_at_first_error() {
+    let mut a = Probe::default();
+    let mut b = Probe::default();
+    {
+        let commands: &mut [(&str, &mut Probe)] = &mut [("AT+A", &mut a), ("AT+B", &mut b)];
+        let mut parser = AtParser::new();
+        parser.set_commands(commands);
+
+        let mut out = String::new();
+        parser.execute_line("AT+A=1;AT+X;AT+B=1", &mut out).unwrap();
+        assert_eq!(out, "ERROR\r\n");
+    }
+    assert_eq!(a.sets, 1);
+    assert_eq!(b.sets, 0);
+}
+
+#[test]
+fn empty_line_is_ignored_by_default() {
+    let mut a = Probe::default();
+    let commands: &mut [(&str, &mut Probe)] = &mut [("AT+A", &mut a)];
+    let mut parser = AtParser::new();
+    parser.set_commands(commands);
+
+    assert_eq!(parser.execute("\r\n"), Ok(""));
+    assert_eq!(parser.execute("   "), Ok(""));
+
+    let mut out = String::new();
+    parser.execute_framed("\r\n", &mut out).unwrap();
+    assert_eq!(out, "");
+}
+
+#[test]
+fn empty_line_can_be_reported_as_error() {
+    let mut a = Probe::default();
+    let commands: &mut [(&str, &mut Probe)] = &mut [("AT+A", &mut a)];
+    let mut parser = AtParser::new();
+    parser.set_commands(commands);
+    parser.set_ignore_empty(false);
+
+    assert_eq!(parser.execute("\r\n"), Err(AtError::UnknownCommand));
+
+    let mut out = String::new();
+    parser.execute_framed("\r\n", &mut out).unwrap();
+    assert_eq!(out, "ERROR\r\n");
+}
+
+#[test]
+fn clear_commands_unregisters_everything() {
+    let mut a = Probe::default();
+    let mut b = Probe::default();
+    let commands: &mut [(&str, &mut Probe)] = &mut [("AT+A", &mut a)];
+    let group: &mut [(&str, &mut Probe)] = &mut [("AT+B", &mut b)];
+    let mut parser = AtParser::new();
+    parser.set_commands(commands);
+    parser.add_group(group).unwrap();
+    assert_eq!(parser.execute("AT+B?"), Ok("1"));
+
+    assert_eq!(parser.len(), 2);
+    assert!(!parser.is_empty());
+
+    parser.clear_commands();
+    assert!(parser.is_empty());
+    assert_eq!(parser.execute("AT+A?"), Err(AtError::UnknownCommand));
+    assert_eq!(parser.execute("AT+B?"), Err(AtError::UnknownCommand));
+    assert_eq!(parser.command_names().count(), 0);
+}
+
+#[test]
+fn command_table_registers_from_iterator() {
+    use at_parser_rs::table::CommandTable;
+
+    let mut a = Probe::default();
+    let mut b = Probe::default();
+    let mut c = Probe::default();
+    let mut table: CommandTable<'_, Probe, 2> = CommandTable::new();
+    table
+        .extend_commands([("AT+A", &mut a), ("AT+B", &mut b)])
+        .unwrap();
+    assert_eq!(table.push("AT+C", &mut c), Err(AtError::BufferFull));
+    assert_eq!(table.len(), 2);
+
+    let mut parser = AtParser::new();
+    parser.set_commands(table.as_mut_slice());
+    assert_eq!(parser.execute("AT+B?"), Ok("1"));
+    assert_eq!(parser.execute("AT+C?"), Err(AtError::UnknownCommand));
+}
+
+/// Module reporting which form was dispatched and with which payload
+struct FormProbe;
+
+impl AtContext for FormProbe {
+    fn exec(&self) -> AtResult<'static> {
+        Ok("exec")
+    }
+
+    fn set(&mut self, args: Args) -> AtResult<'static> {
+        if args.is_empty() {
+            Ok("set-empty")
+        } else {
+            Ok("set")
+        }
+    }
+}
+
+#[test]
+fn trailing_equals_routes_to_set_with_empty_args() {
+    let mut x = FormProbe;
+    let commands: &mut [(&str, &mut FormProbe)] = &mut [("AT+X", &mut x)];
+    let mut parser = AtParser::new();
+    parser.set_commands(commands);
+
+    assert_eq!(parser.execute("AT+X="), Ok("set-empty"));
+    assert_eq!(parser.execute("AT+X"), Ok("exec"));
+    assert_eq!(parser.execute("AT+X=1"), Ok("set"));
+
+    let empty = Args { raw: "" };
+    assert!(empty.is_empty());
+    assert_eq!(empty.len(), 0);
+    assert_eq!(empty.get(0), Some(""));
+}
+
+#[test]
+fn response_prefix_applies_to_exec_and_query() {
+    let mut led = Probe::default();
+    let mut x = FormProbe;
+    let mut parser = AtParser::new();
+    let commands: &mut [(&str, &mut Probe)] = &mut [("AT+LED", &mut led)];
+    parser.set_commands(commands);
+    parser.set_response_prefix(true);
+
+    let mut out = String::new();
+    parser.execute_framed("AT+LED?", &mut out).unwrap();
+    assert_eq!(out, "+LED: 1\r\nOK\r\n");
+
+    let mut out = String::new();
+    parser.execute_framed("AT+LED", &mut out).unwrap();
+    assert_eq!(out, "OK\r\n");
+
+    let mut parser = AtParser::new();
+    let commands: &mut [(&str, &mut FormProbe)] = &mut [("AT+X", &mut x)];
+    parser.set_commands(commands);
+    parser.set_response_prefix(true);
+
+    let mut out = String::new();
+    parser.execute_line("AT+X;AT+X=1", &mut out).unwrap();
+    assert_eq!(out, "+X: exec\r\nset\r\nOK\r\n");
+}
+
+/// Module with a help text
+struct Helped;
+
+impl AtContext for Helped {
+    fn help(&self) -> &'static str {
+        "Set LED\tstate\r\n0/1"
+    }
+}
+
+#[test]
+fn describe_into_lists_commands_with_help() {
+    let mut a = Helped;
+    let mut b = Helped;
+    let commands: &mut [(&str, &mut Helped)] = &mut [("AT+LED", &mut a), ("AT+FAN", &mut b)];
+    let mut parser = AtParser::new();
+    parser.set_commands(commands);
+
+    let mut out = String::new();
+    parser.describe_into(&mut out).unwrap();
+    assert_eq!(out, "AT+LED\tSet LED state  0/1\nAT+FAN\tSet LED state  0/1\n");
+}
+
+/// Multi-instance module answering with the requested index
+#[derive(Default)]
+struct Sockets {
+    last: Option<u32>,
+}
+
+impl AtContext for Sockets {
+    fn query(&mut self) -> AtResult<'static> {
+        Ok("all")
+    }
+
+    fn query_indexed(&mut self, index: u32) -> AtResult<'static> {
+        self.last = Some(index);
+        match index {
+            0..=3 => Ok("open"),
+            _ => Err(AtError::InvalidArgs),
+        }
+    }
+}
+
+#[test]
+fn indexed_commands_pass_the_numeric_suffix() {
+    let mut all = Sockets::default();
+    let mut sock = Sockets::default();
+    {
+        let commands: &mut [(&str, &mut Sockets)] = &mut [("AT+SOCK", &mut all), ("AT+SOCK#", &mut sock)];
+        let mut parser = AtParser::new();
+        parser.set_commands(commands);
+        parser.set_response_prefix(true);
+
+        assert_eq!(parser.execute("AT+SOCK?"), Ok("all"));
+        assert_eq!(parser.execute("AT+SOCK2?"), Ok("open"));
+        assert_eq!(parser.execute("AT+SOCK9?"), Err(AtError::InvalidArgs));
+        assert_eq!(parser.execute("AT+SOCK+1?"), Err(AtError::UnknownCommand));
+        assert_eq!(parser.execute("AT+SOCK99999999999?"), Err(AtError::UnknownCommand));
+        assert_eq!(parser.validate("AT+SOCK1?").map(|(name, _)| name), Ok("AT+SOCK#"));
+
+        let mut out = String::new();
+        parser.execute_framed("AT+SOCK1?", &mut out).unwrap();
+        assert_eq!(out, "+SOCK1: open\r\nOK\r\n");
+    }
+    assert_eq!(all.last, None);
+    assert_eq!(sock.last, Some(1));
+}
+
+#[test]
+fn error_formatter_renders_failed_commands() {
+    use core::fmt::Write;
+
+    fn cme_error(error: AtError, out: &mut dyn Write) -> core::fmt::Result {
+        write!(out, "+CME ERROR: {}\r\n", error.code())
+    }
+
+    let mut a = Probe::default();
+    let commands: &mut [(&str, &mut Probe)] = &mut [("AT+A", &mut a)];
+    let mut parser = AtParser::new();
+    parser.set_commands(commands);
+    parser.set_error_formatter(Some(cme_error));
+
+    let mut out = String::new();
+    parser.execute_framed("AT+X", &mut out).unwrap();
+    parser.execute_line("AT+A?;AT+A=?", &mut out).unwrap();
+    assert_eq!(out, "+CME ERROR: 1\r\n1\r\n+CME ERROR: 2\r\n");
+
+    parser.set_error_formatter(None);
+    let mut out = String::new();
+    parser.execute_framed("AT+X", &mut out).unwrap();
+    assert_eq!(out, "ERROR\r\n");
+}
+
+#[test]
+fn normalizer_runs_before_parsing() {
+    fn skip_to_at(input: &str) -> &str {
+        input.find("AT").map_or(input, |start| &input[start..])
+    }
+
+    let mut a = Probe::default();
+    let commands: &mut [(&str, &mut Probe)] = &mut [("AT+A", &mut a)];
+    let mut parser = AtParser::new();
+    parser.set_commands(commands);
+
+    assert_eq!(parser.execute("\u{7f}#AT+A?"), Err(AtError::UnknownCommand));
+
+    parser.set_normalizer(Some(skip_to_at));
+    assert_eq!(parser.execute("\u{7f}#AT+A?"), Ok("1"));
+
+    let mut out = String::new();
+    parser.execute_line("~AT+A?; ~~AT+A?", &mut out).unwrap();
+    assert_eq!(out, "1\r\n1\r\nOK\r\n");
+}
+
+#[test]
+fn query_all_into_dumps_every_queryable_command() {
+    let mut led = Probe::default();
+    let mut x = Probe::default();
+    let mut sock = Sockets::default();
+    let mut fan = Helped;
+
+    let mut parser = AtParser::new();
+    let commands: &mut [(&str, &mut Probe)] = &mut [("AT+LED", &mut led), ("AT&X", &mut x)];
+    parser.set_commands(commands);
+    let mut out = String::new();
+    parser.query_all_into(&mut out).unwrap();
+    assert_eq!(out, "+LED: 1\r\n+X: 1\r\n");
+
+    let mut parser = AtParser::new();
+    let commands: &mut [(&str, &mut Sockets)] = &mut [("AT+SOCK#", &mut sock)];
+    parser.set_commands(commands);
+    let mut out = String::new();
+    parser.query_all_into(&mut out).unwrap();
+    assert_eq!(out, "");
+
+    let mut parser = AtParser::new();
+    let commands: &mut [(&str, &mut Helped)] = &mut [("AT+FAN", &mut fan)];
+    parser.set_commands(commands);
+    let mut out = String::new();
+    parser.query_all_into(&mut out).unwrap();
+    assert_eq!(out, "");
+}
+
+/// Module with two coupled fields updated by a single Set
+#[derive(Default)]
+struct Window {
+    min: u32,
+    max: u32,
+}
+
+impl AtContext for Window {
+    fn validate_set(&self, args: Args) -> Result<(), AtError> {
+        args.expect(2, 2)?;
+        if args.get_u32(0)? > args.get_u32(1)? {
+            return Err(AtError::InvalidArgs);
+        }
+        Ok(())
+    }
+
+    fn set(&mut self, args: Args) -> AtResult<'static> {
+        self.min = args.get_u32(0)?;
+        self.max = args.get_u32(1)?;
+        Ok("")
+    }
+}
+
+#[test]
+fn validate_set_runs_before_set() {
+    let mut window = Window::default();
+    {
+        let commands: &mut [(&str, &mut Window)] = &mut [("AT+WIN", &mut window)];
+        let mut parser = AtParser::new();
+        parser.set_commands(commands);
+
+        assert_eq!(parser.execute("AT+WIN=10,20"), Ok(""));
+        assert_eq!(parser.execute("AT+WIN=30,x"), Err(AtError::InvalidArgs));
+        assert_eq!(parser.execute("AT+WIN=30,25"), Err(AtError::InvalidArgs));
+    }
+    assert_eq!((window.min, window.max), (10, 20));
+}
+
+#[test]
+fn suggest_returns_the_longest_shared_prefix() {
+    let mut a = Probe::default();
+    let mut b = Probe::default();
+    let mut c = Probe::default();
+    let commands: &mut [(&str, &mut Probe)] = &mut [("AT+WIFI", &mut a), ("AT+WIFISCAN", &mut b), ("AT+LED", &mut c)];
+    let mut parser = AtParser::new();
+    parser.set_commands(commands);
+
+    assert_eq!(parser.suggest("AT+WIFISCNA?"), Some("AT+WIFISCAN"));
+    assert_eq!(parser.suggest("AT+wif=1"), Some("AT+WIFI"));
+    assert_eq!(parser.suggest("AT+LD"), Some("AT+LED"));
+    assert_eq!(parser.suggest("AT+X"), None);
+}
+
+#[test]
+fn command_names_can_be_built_at_runtime() {
+    let serial = 42;
+    let name = format!("AT+DEV{}", serial);
+
+    let mut a = Probe::default();
+    let commands: &mut [(&str, &mut Probe)] = &mut [(name.as_str(), &mut a)];
+    let mut parser = AtParser::new();
+    parser.set_commands(commands);
+
+    assert_eq!(parser.execute("AT+DEV42?"), Ok("1"));
+    assert_eq!(parser.command_names().collect::<Vec<_>>(), ["AT+DEV42"]);
+}
+
+/// Module implementing only exec, with a help text
+struct ExecOnly;
+
+impl AtContext for ExecOnly {
+    fn exec(&self) -> AtResult<'static> {
+        Ok("ready")
+    }
+
+    fn help(&self) -> &'static str {
+        "Device status"
+    }
+}
+
+#[test]
+fn form_defaults_answer_unsupported_forms() {
+    use at_parser_rs::parser::FormDefaults;
+
+    let mut status = ExecOnly;
+    let mut led = Probe::default();
+    let mut parser = AtParser::new();
+    let commands: &mut [(&str, &mut ExecOnly)] = &mut [("AT+STATUS", &mut status)];
+    parser.set_commands(commands);
+
+    assert_eq!(parser.execute("AT+STATUS?"), Err(AtError::NotSupported));
+    assert_eq!(parser.execute("AT+STATUS=?"), Err(AtError::NotSupported));
+
+    parser.set_form_defaults(FormDefaults { query_from_exec: true, test_from_help: true });
+    assert_eq!(parser.execute("AT+STATUS?"), Ok("ready"));
+    assert_eq!(parser.execute("AT+STATUS=?"), Ok("Device status"));
+    assert_eq!(parser.execute("AT+STATUS=1"), Err(AtError::NotSupported));
+
+    let mut out = String::new();
+    parser.execute_framed("AT+STATUS?", &mut out).unwrap();
+    assert_eq!(out, "ready\r\nOK\r\n");
+
+    // The module's own handler takes precedence over the default
+    let mut parser = AtParser::new();
+    let commands: &mut [(&str, &mut Probe)] = &mut [("AT+LED", &mut led)];
+    parser.set_commands(commands);
+    parser.set_form_defaults(FormDefaults { query_from_exec: true, test_from_help: false });
+    assert_eq!(parser.execute("AT+LED?"), Ok("1"));
+}
+
+/// Module streaming its own output on exec
+struct Updater;
+
+impl AtContext for Updater {
+    fn exec(&self) -> AtResult<'static> {
+        Ok(at_parser_rs::SUPPRESSED)
+    }
+}
+
+#[test]
+fn suppressed_responses_emit_nothing() {
+    let mut fw = Updater;
+    let commands: &mut [(&str, &mut Updater)] = &mut [("AT+FWUPD", &mut fw)];
+    let mut parser = AtParser::new();
+    parser.set_commands(commands);
+    parser.set_response_prefix(true);
+    parser.set_response_filter(Some(|_| "filtered"));
+
+    let mut out = String::new();
+    parser.execute_framed("AT+FWUPD", &mut out).unwrap();
+    assert_eq!(out, "");
+
+    parser.execute_line("AT+FWUPD;AT+FWUPD", &mut out).unwrap();
+    assert_eq!(out, "OK\r\n");
+
+    // The marker never reaches callers of the plain path
+    assert_eq!(parser.execute("AT+FWUPD"), Ok(""));
+    assert_eq!(parser.execute_bytes(b"AT+FWUPD"), Ok(""));
+    let mut buf = [0xFFu8; 4];
+    assert_eq!(parser.execute_bytes_framed(b"AT+FWUPD", &mut buf), Ok(0));
+}
+
+#[test]
+fn command_mut_returns_the_registered_handler() {
+    let mut a = Probe::default();
+    {
+        let commands: &mut [(&str, &mut Probe)] = &mut [("AT+A", &mut a)];
+        let mut parser = AtParser::new();
+        parser.set_commands(commands);
+
+        let module = parser.command_mut("AT+A").unwrap();
+        assert_eq!(module.set(Args { raw: "1" }), Ok(""));
+        assert!(parser.command_mut("AT+B").is_none());
+    }
+    assert_eq!(a.sets, 1);
+}
+
+/// Module rendering its test answer from a configurable limit
+struct Dimmer {
+    max: u32,
+}
+
+impl AtContext for Dimmer {
+    fn test_into(&mut self, out: &mut dyn core::fmt::Write) -> Result<(), AtError> {
+        at_parser_rs::response::write_template(out, "+DIM: (0-{max})\r\n", &[("max", &self.max)])
+            .map_err(|_| AtError::BufferFull)
+    }
+}
+
+#[test]
+fn test_into_renders_on_the_framed_path() {
+    let mut dim = Dimmer { max: 80 };
+    let commands: &mut [(&str, &mut Dimmer)] = &mut [("AT+DIM", &mut dim)];
+    let mut parser = AtParser::new();
+    parser.set_commands(commands);
+
+    let mut out = String::new();
+    parser.execute_framed("AT+DIM=?", &mut out).unwrap();
+    assert_eq!(out, "+DIM: (0-80)\r\nOK\r\n");
+    assert_eq!(parser.execute("AT+DIM=?"), Err(AtError::NotSupported));
+}
+
+#[test]
+fn terminator_keywords_are_configurable() {
+    let mut a = Probe::default();
+    let commands: &mut [(&str, &mut Probe)] = &mut [("AT+A", &mut a)];
+    let mut parser = AtParser::new();
+    parser.set_commands(commands);
+    parser.set_ok_keyword("DONE");
+    parser.set_error_keyword("FAIL");
+
+    let mut out = String::new();
+    parser.execute_framed("AT+A?", &mut out).unwrap();
+    parser.execute_framed("AT+X", &mut out).unwrap();
+    parser.execute_line("AT+A;AT+A", &mut out).unwrap();
+    assert_eq!(out, "1\r\nDONE\r\nFAIL\r\nDONE\r\n");
+}
+
+/// Dialer recording the last dialed number
+#[derive(Default)]
+struct Dialer {
+    number: String,
+}
+
+impl AtContext for Dialer {
+    fn set(&mut self, args: Args) -> AtResult<'static> {
+        self.number = args.require(0)?.to_owned();
+        Ok("")
+    }
+}
+
+#[test]
+fn space_separated_args_route_to_set_when_enabled() {
+    let mut dialer = Dialer::default();
+    {
+        let commands: &mut [(&str, &mut Dialer)] = &mut [("AT+DIAL", &mut dialer)];
+        let mut parser = AtParser::new();
+        parser.set_commands(commands);
+
+        assert_eq!(parser.execute("AT+DIAL 555"), Err(AtError::UnknownCommand));
+
+        parser.set_space_args(true);
+        assert_eq!(parser.execute("AT+DIAL  555"), Ok(""));
+        assert_eq!(parser.execute("AT+DIAL"), Err(AtError::NotSupported));
+    }
+    assert_eq!(dialer.number, "555");
+}
+
+#[test]
+fn observer_sees_every_executed_command() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static MATCHED: AtomicUsize = AtomicUsize::new(0);
+    static FAILED: AtomicUsize = AtomicUsize::new(0);
+    static SUPPRESSED: AtomicUsize = AtomicUsize::new(0);
+
+    fn observe(input: &str, name: Option<&str>, result: &AtResult<'static>) {
+        // The internal marker is reported as an empty success
+        assert_ne!(*result, Ok(at_parser_rs::SUPPRESSED));
+        if name == Some("AT+FWUPD") {
+            assert_eq!(*result, Ok(""));
+            SUPPRESSED.fetch_add(1, Ordering::Relaxed);
+        } else if name.is_some() {
+            assert!(input.starts_with("AT+A"));
+            MATCHED.fetch_add(1, Ordering::Relaxed);
+        }
+        if result.is_err() {
+            FAILED.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    let mut a = Probe::default();
+    let commands: &mut [(&str, &mut Probe)] = &mut [("AT+A", &mut a)];
+    let mut parser = AtParser::new();
+    parser.set_commands(commands);
+    parser.set_observer(Some(observe));
+
+    assert_eq!(parser.execute("AT+A?"), Ok("1"));
+    assert_eq!(parser.execute("AT+X"), Err(AtError::UnknownCommand));
+    assert_eq!(parser.execute(""), Ok(""));
+    let mut out = String::new();
+    parser.execute_line("AT+A;AT+A=", &mut out).unwrap();
+
+    let mut fw = Updater;
+    let commands: &mut [(&str, &mut Updater)] = &mut [("AT+FWUPD", &mut fw)];
+    let mut parser = AtParser::new();
+    parser.set_commands(commands);
+    parser.set_observer(Some(observe));
+    assert_eq!(parser.execute("AT+FWUPD"), Ok(""));
+    parser.execute_framed("AT+FWUPD", &mut out).unwrap();
+
+    assert_eq!(MATCHED.load(Ordering::Relaxed), 3);
+    assert_eq!(FAILED.load(Ordering::Relaxed), 1);
+    assert_eq!(SUPPRESSED.load(Ordering::Relaxed), 2);
+}
+
+#[test]
+fn names_longer_than_any_registered_command_are_unknown() {
+    let mut a = Probe::default();
+    let mut b = Probe::default();
+    let mut sock = Sockets::default();
+    let commands: &mut [(&str, &mut Probe)] = &mut [("AT+A", &mut a)];
+    let group: &mut [(&str, &mut Probe)] = &mut [("AT+LONGER", &mut b)];
+    let mut parser = AtParser::new();
+    parser.set_commands(commands);
+
+    assert_eq!(parser.execute("AT+LONGER?"), Err(AtError::UnknownCommand));
+    parser.add_group(group).unwrap();
+    assert_eq!(parser.execute("AT+LONGER?"), Ok("1"));
+    assert_eq!(parser.execute("AT+LONGEST?"), Err(AtError::UnknownCommand));
+    assert_eq!(parser.validate("AT+LONGEST?"), Err(AtError::UnknownCommand));
+
+    parser.clear_commands();
+    assert_eq!(parser.execute("AT+A?"), Err(AtError::UnknownCommand));
+
+    let indexed: &mut [(&str, &mut Sockets)] = &mut [("AT+SOCK#", &mut sock)];
+    let mut parser = AtParser::new();
+    parser.set_commands(indexed);
+    assert_eq!(parser.execute("AT+SOCK00000000000001?"), Ok("open"));
+}
+
+struct Flash;
+
+impl AtContext for Flash {
+    fn exec(&self) -> AtResult<'static> {
+        Ok("text")
+    }
+
+    fn bytes(&mut self, form: &AtForm) -> Result<&'static [u8], AtError> {
+        match form {
+            AtForm::Set(args) if args.matches(0, "raw") => Ok(b"\x00\xff\r\n"),
+            AtForm::Set(args) if args.matches(0, "text") => Ok(b"abc"),
+            AtForm::Set(_) => Err(AtError::InvalidArgs),
+            _ => Err(AtError::NotSupported),
+        }
+    }
+}
+
+#[test]
+fn binary_payloads_are_copied_verbatim_into_byte_buffers() {
+    let mut flash = Flash;
+    let commands: &mut [(&str, &mut Flash)] = &mut [("AT+FREAD", &mut flash)];
+    let mut parser = AtParser::new();
+    parser.set_commands(commands);
+
+    let mut buf = [0u8; 32];
+    let len = parser.execute_into("AT+FREAD=raw", &mut buf).unwrap();
+    assert_eq!(&buf[..len], b"\x00\xff\r\n\r\nOK\r\n");
+    let len = parser.execute_into("AT+FREAD", &mut buf).unwrap();
+    assert_eq!(&buf[..len], b"text\r\nOK\r\n");
+    assert_eq!(parser.execute_into("AT+FREAD=raw", &mut buf[..8]), Err(AtError::BufferFull));
+
+    let mut out = String::new();
+    parser.execute_framed("AT+FREAD=text", &mut out).unwrap();
+    parser.execute_framed("AT+FREAD=raw", &mut out).unwrap();
+    parser.execute_framed("AT+FREAD=bad", &mut out).unwrap();
+    assert_eq!(out, "abc\r\nOK\r\nERROR\r\nERROR\r\n");
+
+    out.clear();
+    parser.execute_line("AT+FREAD=text;AT+FREAD=raw", &mut out).unwrap();
+    assert_eq!(out, "abc\r\nERROR\r\n");
+}
+
+#[test]
+fn quiet_mode_omits_result_codes() {
+    let mut a = Probe::default();
+    let commands: &mut [(&str, &mut Probe)] = &mut [("AT+A", &mut a)];
+    let mut parser = AtParser::new();
+    parser.set_commands(commands);
+    parser.set_quiet(true);
+
+    let mut out = String::new();
+    parser.execute_framed("AT+A?", &mut out).unwrap();
+    parser.execute_framed("AT+A", &mut out).unwrap();
+    parser.execute_framed("AT+X", &mut out).unwrap();
+    parser.execute_line("AT+A?;AT+A=", &mut out).unwrap();
+    assert_eq!(out, "1\r\n1\r\n");
+    assert_eq!(parser.execute("AT+X"), Err(AtError::UnknownCommand));
+
+    let mut buf = [0u8; 8];
+    assert_eq!(parser.execute_into("AT+A", &mut buf), Ok(0));
+
+    parser.set_quiet(false);
+    out.clear();
+    parser.execute_framed("AT+A", &mut out).unwrap();
+    assert_eq!(out, "OK\r\n");
+}
+
+#[test]
+fn settings_can_be_saved_and_restored() {
+    let mut a = Probe::default();
+    let commands: &mut [(&str, &mut Probe)] = &mut [("AT+A", &mut a)];
+    let mut parser = AtParser::new();
+    parser.set_commands(commands);
+    parser.set_response_prefix(true);
+
+    let saved: ParserSettings = parser.settings();
+    parser.set_quiet(true);
+    parser.set_ok_keyword("DONE");
+    parser.set_response_prefix(false);
+    assert_ne!(parser.settings(), saved);
+
+    parser.apply_settings(saved);
+    assert_eq!(parser.settings(), saved);
+    let mut out = String::new();
+    parser.execute_framed("AT+A?", &mut out).unwrap();
+    assert_eq!(out, "+A: 1\r\nOK\r\n");
+}
+
+#[test]
+fn secondary_table_is_searched_after_the_primary_one() {
+    let mut core = Probe::default();
+    let mut vendor = Probe::default();
+    let mut extra = Probe::default();
+    {
+        let primary: &mut [(&str, &mut Probe)] = &mut [("AT+A", &mut core)];
+        let secondary: &mut [(&str, &mut Probe)] = &mut [("AT+A", &mut vendor), ("AT+VND", &mut extra)];
+        let mut parser = AtParser::new();
+        parser.set_secondary_commands(secondary);
+        assert_eq!(parser.execute("AT+A=1"), Ok(""));
+
+        parser.set_commands(primary);
+        assert_eq!(parser.execute("AT+A=1"), Ok(""));
+        assert_eq!(parser.execute("AT+VND=1"), Ok(""));
+        assert_eq!(parser.command_names().collect::<Vec<_>>(), ["AT+A", "AT+A", "AT+VND"]);
+
+        parser.clear_commands();
+        assert_eq!(parser.execute("AT+VND=1"), Err(AtError::UnknownCommand));
+    }
+    assert_eq!((core.sets, vendor.sets, extra.sets), (1, 1, 1));
+}
+
+#[test]
+fn set_delimiter_is_configurable() {
+    let mut a = Probe::default();
+    let mut sock = Sockets::default();
+    {
+        let commands: &mut [(&str, &mut Probe)] = &mut [("AT+A", &mut a)];
+        let mut parser = AtParser::new();
+        parser.set_commands(commands);
+        parser.set_set_delimiter('#');
+
+        assert_eq!(parser.execute("AT+A#1"), Ok(""));
+        assert_eq!(parser.execute("AT+A#1=2"), Ok(""));
+        assert_eq!(parser.execute("AT+A=1"), Err(AtError::UnknownCommand));
+        assert_eq!(parser.execute("AT+A##1"), Err(AtError::SyntaxAt(5)));
+        assert_eq!(parser.execute("AT+A?"), Ok("1"));
+        assert_eq!(parser.settings().set_delimiter, '#');
+
+        parser.set_set_delimiter('=');
+        assert_eq!(parser.execute("AT+A=1"), Ok(""));
+    }
+    assert_eq!(a.sets, 3);
+
+    let indexed: &mut [(&str, &mut Sockets)] = &mut [("AT+SOCK#", &mut sock)];
+    let mut parser = AtParser::new();
+    parser.set_commands(indexed);
+    parser.set_set_delimiter('#');
+    assert_eq!(parser.execute("AT+SOCK2?"), Ok("open"));
+}
+
+#[test]
+fn dispatch_runs_a_single_module_without_a_table() {
+    let mut probe = Probe::default();
+    assert_eq!(dispatch(&mut probe, "AT+A", "AT+A?"), Ok("1"));
+    assert_eq!(dispatch(&mut probe, "AT+A", " AT+A=5\r\n"), Ok(""));
+    assert_eq!(dispatch(&mut probe, "AT+A", "AT+B=5"), Err(AtError::UnknownCommand));
+    assert_eq!(dispatch(&mut probe, "AT+A", "AT+AB?"), Err(AtError::UnknownCommand));
+    assert_eq!(dispatch(&mut probe, "AT+A", "AT+A==5"), Err(AtError::SyntaxAt(5)));
+    assert_eq!(probe.sets, 1);
+
+    let mut sock = Sockets::default();
+    assert_eq!(dispatch(&mut sock, "AT+SOCK#", "AT+SOCK3?"), Ok("open"));
+    assert_eq!(sock.last, Some(3));
+}
+
+struct Sensor;
+
+impl AtContext for Sensor {
+    fn exec(&self) -> AtResult<'static> {
+        Err(AtError::Message("sensor not calibrated"))
+    }
+}
+
+#[test]
+fn message_errors_carry_their_text() {
+    let error = AtError::Message("sensor not calibrated");
+    assert_eq!(error.to_string(), "sensor not calibrated");
+    assert_eq!(error.code(), 100);
+    assert_ne!(error, AtError::Message("other"));
+
+    let mut sensor = Sensor;
+    let commands: &mut [(&str, &mut Sensor)] = &mut [("AT+TEMP", &mut sensor)];
+    let mut parser = AtParser::new();
+    parser.set_commands(commands);
+
+    let mut out = String::new();
+    parser.execute_framed("AT+TEMP", &mut out).unwrap();
+    parser.execute_framed("AT+NOPE", &mut out).unwrap();
+    assert_eq!(out, "ERROR: sensor not calibrated\r\nERROR\r\n");
+}
+
+/// Module answering parameterized tests
+struct Modes;
+
+impl AtContext for Modes {
+    fn test(&mut self) -> AtResult<'static> {
+        Ok("(0-2)")
+    }
+
+    fn test_with(&mut self, args: Args) -> AtResult<'static> {
+        match args.get_u32(0)? {
+            0 => Ok("(1-10)"),
+            1 => Ok("(1-100)"),
+            _ => Err(AtError::InvalidArgs),
+        }
+    }
+}
+
+#[test]
+fn test_form_with_arguments_reaches_test_with() {
+    let mut modes = Modes;
+    let mut probe = Probe::default();
+    {
+        let commands: &mut [(&str, &mut Modes)] = &mut [("AT+MODE", &mut modes)];
+        let mut parser = AtParser::new();
+        parser.set_commands(commands);
+
+        assert_eq!(parser.execute("AT+MODE=?"), Ok("(0-2)"));
+        assert_eq!(parser.execute("AT+MODE=1?"), Ok("(1-100)"));
+        assert_eq!(parser.execute("AT+MODE=7?"), Err(AtError::InvalidArgs));
+        assert_eq!(parser.validate("AT+MODE=0?"), Ok(("AT+MODE", FormKind::Test)));
+
+        let mut out = String::new();
+        parser.execute_framed("AT+MODE=0?", &mut out).unwrap();
+        assert_eq!(out, "(1-10)\r\nOK\r\n");
+    }
+
+    // The default implementation ignores the arguments
+    assert_eq!(dispatch(&mut probe, "AT+A", "AT+A=1?"), Err(AtError::NotSupported));
+}
+
+#[test]
+fn free_text_ending_with_question_mark_is_a_set() {
+    let mut a = Probe::default();
+    {
+        let commands: &mut [(&str, &mut Probe)] = &mut [("AT+A", &mut a)];
+        let mut parser = AtParser::new();
+        parser.set_commands(commands);
+
+        assert_eq!(parser.validate("AT+A=hello?"), Ok(("AT+A", FormKind::Set)));
+        assert_eq!(parser.execute("AT+A=hello?"), Ok(""));
+        assert_eq!(parser.execute("AT+A=http://x/?"), Ok(""));
+        assert_eq!(parser.execute("AT+A=\"1\"?"), Ok(""));
+        assert_eq!(parser.validate("AT+A=1,-2?"), Ok(("AT+A", FormKind::Test)));
+
+        parser.set_set_delimiter('#');
+        assert_eq!(parser.validate("AT+A#1?"), Ok(("AT+A", FormKind::Test)));
+        assert_eq!(parser.validate("AT+A#?"), Ok(("AT+A", FormKind::Test)));
+        assert!(matches!(at_parser_rs::parser::parse("AT+A= ?"), Ok(("AT+A", AtForm::Set(_)))));
+        assert_eq!(parser.execute("AT+A#why?"), Ok(""));
+        assert_eq!(parser.validate("AT+A=?"), Ok(("AT+A", FormKind::Test)));
+    }
+    assert_eq!(a.sets, 4);
+}
+
+#[test]
+fn buffered_parser_owns_its_response_buffer() {
+    use at_parser_rs::buffered::BufferedAtParser;
+
+    let mut a = Probe::default();
+    let commands: &mut [(&str, &mut Probe)] = &mut [("AT+A", &mut a)];
+    let mut parser = AtParser::new();
+    parser.set_commands(commands);
+
+    let mut buffered: BufferedAtParser<'_, Probe, 16> = BufferedAtParser::new(parser);
+    assert_eq!(buffered.execute("AT+A?"), Ok("1\r\nOK\r\n"));
+    assert_eq!(buffered.execute("AT+X"), Ok("ERROR\r\n"));
+    assert_eq!(buffered.execute_line("AT+A?;AT+A?"), Ok("1\r\n1\r\nOK\r\n"));
+    assert_eq!(buffered.execute_line("AT+A?;AT+A?;AT+A?;AT+A?;AT+A?"), Err(AtError::BufferFull));
+
+    buffered.parser_mut().set_response_prefix(true);
+    assert_eq!(buffered.execute("AT+A?"), Ok("+A: 1\r\nOK\r\n"));
+    assert_eq!(buffered.parser().len(), 1);
+}
+
+/// Module only accepted while connected
+struct Send;
+
+const DISCONNECTED: u8 = 0;
+const CONNECTED: u8 = 1;
+
+impl AtContext for Send {
+    fn exec(&self) -> AtResult<'static> {
+        Ok("SENT")
+    }
+
+    fn state_mask(&self) -> u32 {
+        1 << CONNECTED
+    }
+}
+
+#[test]
+fn commands_are_rejected_outside_their_states() {
+    let mut send = Send;
+    let commands: &mut [(&str, &mut Send)] = &mut [("AT+SEND", &mut send)];
+    let mut parser = AtParser::new();
+    parser.set_commands(commands);
+
+    assert_eq!(parser.state(), DISCONNECTED);
+    assert_eq!(parser.execute("AT+SEND"), Err(AtError::NotSupported));
+    parser.set_state(CONNECTED);
+    assert_eq!(parser.execute("AT+SEND"), Ok("SENT"));
+    parser.set_state(40);
+    assert_eq!(parser.execute("AT+SEND"), Err(AtError::NotSupported));
+}
+
+/// Module opening a data session until `BYE` is received
+#[derive(Default)]
+struct Session {
+    dialing: bool,
+    received: usize,
+}
+
+impl AtContext for Session {
+    fn set(&mut self, _args: Args) -> AtResult<'static> {
+        self.dialing = true;
+        Ok("")
+    }
+
+    fn data_phase(&mut self) -> Option<&'static str> {
+        core::mem::take(&mut self.dialing).then_some("CONNECT")
+    }
+
+    fn data(&mut self, bytes: &[u8]) -> Option<AtResult<'static>> {
+        if bytes == b"BYE" {
+            return Some(Ok("NO CARRIER"));
+        }
+        self.received += bytes.len();
+        None
+    }
+}
+
+#[test]
+fn data_phase_emits_an_intermediate_result() {
+    let mut session = Session::default();
+    {
+        let commands: &mut [(&str, &mut Session)] = &mut [("ATD", &mut session)];
+        let mut parser = AtParser::new();
+        parser.set_commands(commands);
+
+        let mut out = String::new();
+        parser.execute_line("ATD=555;ATD=1", &mut out).unwrap();
+        assert_eq!(out, "CONNECT\r\n");
+        assert!(parser.data_mode());
+
+        out.clear();
+        parser.feed_data(b"hello", &mut out).unwrap();
+        parser.feed_data(b"!", &mut out).unwrap();
+        assert_eq!(out, "");
+        parser.feed_data(b"BYE", &mut out).unwrap();
+        assert_eq!(out, "NO CARRIER\r\nOK\r\n");
+        assert!(!parser.data_mode());
+
+        out.clear();
+        parser.feed_data(b"ignored", &mut out).unwrap();
+        parser.execute_framed("ATD", &mut out).unwrap();
+        assert_eq!(out, "ERROR\r\n");
+    }
+    assert_eq!(session.received, 6);
+}
+
+#[test]
+fn checked_registration_rejects_long_names() {
+    use at_parser_rs::parser::MAX_NAME_LEN;
+
+    let mut a = Probe::default();
+    let mut b = Probe::default();
+    let short: &mut [(&str, &mut Probe)] = &mut [("AT+A", &mut a)];
+    let long: &mut [(&str, &mut Probe)] = &mut [("AT+LONGNAME", &mut b)];
+    let mut parser = AtParser::new();
+
+    assert_eq!(parser.set_commands_checked::<MAX_NAME_LEN>(short), Ok(()));
+    assert_eq!(parser.set_commands_checked::<8>(long), Err(ConfigError::NameTooLong("AT+LONGNAME")));
+    assert_eq!(parser.command_names().collect::<Vec<_>>(), ["AT+A"]);
+}
+
+#[test]
+fn escape_sequence_returns_to_command_mode() {
+    let mut session = Session::default();
+    {
+        let commands: &mut [(&str, &mut Session)] = &mut [("ATD", &mut session)];
+        let mut parser = AtParser::new();
+        parser.set_commands(commands);
+
+        let mut out = String::new();
+        parser.execute_framed("ATD=1", &mut out).unwrap();
+        out.clear();
+
+        // Escapes embedded in data or broken by data are plain data
+        parser.feed_data(b"a+++", &mut out).unwrap();
+        parser.feed_data(b"++", &mut out).unwrap();
+        parser.feed_data(b"b", &mut out).unwrap();
+        assert!(parser.data_mode());
+
+        parser.feed_data(b"+", &mut out).unwrap();
+        parser.feed_data(b"++", &mut out).unwrap();
+        assert!(!parser.data_mode());
+        assert_eq!(out, "OK\r\n");
+
+        parser.set_escape_sequence(Some(b"~~"));
+        parser.execute_framed("ATD=1", &mut out).unwrap();
+        parser.feed_data(b"+++", &mut out).unwrap();
+        assert!(parser.data_mode());
+        parser.feed_data(b"~~", &mut out).unwrap();
+        assert!(!parser.data_mode());
+    }
+    assert_eq!(session.received, 10);
+}
+
+#[test]
+fn self_check_reports_misconfigured_tables() {
+    let (mut a, mut b, mut c) = (Probe::default(), Probe::default(), Probe::default());
+    let (mut d, mut e, mut f, mut g) = (Probe::default(), Probe::default(), Probe::default(), Probe::default());
+    let long = "AT+ABCDEFGHIJKLMNOPQRSTUVWXYZABCDEF";
+    let empty: &mut [(&str, &mut Probe)] = &mut [("AT+A", &mut a), ("", &mut b)];
+    let too_long: &mut [(&str, &mut Probe)] = &mut [("AT+A", &mut d), (long, &mut e)];
+    let duplicate: &mut [(&str, &mut Probe)] = &mut [("AT+A", &mut f), ("AT+B", &mut g)];
+    let secondary: &mut [(&str, &mut Probe)] = &mut [("AT+A", &mut c)];
+    let mut parser = AtParser::new();
+    assert_eq!(parser.self_check(), Ok(()));
+
+    parser.set_secondary_commands(secondary);
+    assert_eq!(parser.self_check(), Ok(()));
+    parser.set_commands(empty);
+    assert_eq!(parser.self_check(), Err(ConfigError::EmptyName));
+    parser.set_commands(too_long);
+    assert_eq!(parser.self_check(), Err(ConfigError::NameTooLong(long)));
+    parser.set_commands(duplicate);
+    assert_eq!(parser.self_check(), Err(ConfigError::DuplicateName("AT+A")));
+    assert_eq!(parser.commands().iter().map(|(name, _)| *name).collect::<Vec<_>>(), ["AT+A", "AT+B"]);
+}
+
+#[test]
+fn command_parser_self_check_requires_a_handler() {
+    fn exec(_: &mut ()) -> AtResult<'static> {
+        Ok("")
+    }
+
+    let commands = [AtCommand::named("AT+X").exec(exec), AtCommand::named("AT+H").help("AT+H")];
+    assert_eq!(AtCommandParser::new(&commands).self_check(), Ok(()));
+
+    let commands = [AtCommand::named("AT+X").exec(exec), AtCommand::named("AT+Y")];
+    assert_eq!(AtCommandParser::new(&commands).self_check(), Err(ConfigError::NoHandler("AT+Y")));
+
+    let commands = [AtCommand::named("AT+X").exec(exec), AtCommand::named("AT+X").exec(exec)];
+    assert_eq!(AtCommandParser::new(&commands).self_check(), Err(ConfigError::DuplicateName("AT+X")));
+}
+
+#[test]
+fn busy_parser_rejects_commands_until_cleared() {
+    let mut a = Probe::default();
+    {
+        let commands: &mut [(&str, &mut Probe)] = &mut [("AT+A", &mut a)];
+        let mut parser = AtParser::new();
+        parser.set_commands(commands);
+        assert!(!parser.is_busy());
+
+        parser.set_busy(true);
+        assert_eq!(parser.execute("AT+A=1"), Err(AtError::Busy));
+        assert_eq!(parser.execute("AT+NOPE"), Err(AtError::Busy));
+        assert_eq!(parser.execute(""), Ok(""));
+        let mut out = String::new();
+        parser.execute_framed("AT+A?", &mut out).unwrap();
+        assert_eq!(out, "ERROR\r\n");
+
+        parser.set_busy(false);
+        assert_eq!(parser.execute("AT+A=1"), Ok(""));
+    }
+    assert_eq!(a.sets, 1);
+    assert_eq!(AtError::Busy.code(), 9);
+}
+
+#[test]
+fn bare_at_ping_is_answered_by_the_ping_handler() {
+    /// Module counting the pings it answers
+    #[derive(Default)]
+    struct Ping {
+        count: core::cell::Cell<u32>,
+    }
+
+    impl AtContext for Ping {
+        fn exec(&self) -> AtResult<'static> {
+            self.count.set(self.count.get() + 1);
+            Ok("PONG")
+        }
+    }
+
+    let mut a = Probe::default();
+    let mut ping = Ping::default();
+    {
+        let commands: &mut [(&str, &mut Probe)] = &mut [("AT+A", &mut a)];
+        let mut parser = AtParser::new();
+        parser.set_commands(commands);
+
+        let mut out = String::new();
+        parser.execute_framed("AT", &mut out).unwrap();
+        assert_eq!(out, "OK\r\n");
+        assert_eq!(parser.execute("AT?"), Err(AtError::SyntaxAt(2)));
+
+        parser.set_ping_handler(Some(&mut ping));
+        assert_eq!(parser.execute("AT"), Ok("PONG"));
+        assert_eq!(parser.execute("AT+B"), Err(AtError::UnknownCommand));
+    }
+    assert_eq!(ping.count.get(), 1);
+}
+
+#[test]
+fn bytes_framed_writes_a_single_result_byte() {
+    let mut a = Probe::default();
+    let commands: &mut [(&str, &mut Probe)] = &mut [("AT+A", &mut a)];
+    let mut parser = AtParser::new();
+    parser.set_commands(commands);
+
+    let mut buf = [0u8; 8];
+    assert_eq!(parser.execute_bytes_framed(b"AT+A=1", &mut buf), Ok(1));
+    assert_eq!(buf[0], 0x00);
+    assert_eq!(parser.execute_bytes_framed(b"AT+A?", &mut buf), Ok(2));
+    assert_eq!(&buf[..2], b"\x001");
+    assert_eq!(parser.execute_bytes_framed(b"AT+B", &mut buf), Ok(1));
+    assert_eq!(buf[0], 0x01);
+    assert_eq!(parser.execute_bytes_framed(b"AT+A\xff", &mut buf), Ok(1));
+    assert_eq!(buf[0], 0x06);
+    assert_eq!(parser.execute_bytes_framed(b"  ", &mut buf), Ok(0));
+    assert_eq!(parser.execute_bytes_framed(b"AT+A?", &mut buf[..1]), Err(AtError::BufferFull));
+}
+
+#[test]
+fn remapped_commands_reach_the_new_handler() {
+    let mut old = Probe::default();
+    let mut new = Probe::default();
+    {
+        let commands: &mut [(&str, &mut Probe)] = &mut [("AT+OLD", &mut old), ("AT+NEW", &mut new)];
+        let mut parser = AtParser::new();
+        parser.set_commands(commands);
+        assert_eq!(parser.add_remap("AT+OLD", "AT+NEW"), Ok(()));
+
+        assert_eq!(parser.execute("AT+OLD=1"), Ok(""));
+        assert_eq!(parser.execute("AT+NEW=1"), Ok(""));
+        assert_eq!(parser.validate("AT+OLD?"), Ok(("AT+NEW", FormKind::Query)));
+        assert_eq!(parser.execute("AT+OL=1"), Err(AtError::UnknownCommand));
+
+        parser.add_remap("AT+OLD", "AT+GONE").unwrap();
+        assert_eq!(parser.execute("AT+OLD=1"), Err(AtError::UnknownCommand));
+        for old in ["AT+1", "AT+2", "AT+3", "AT+4", "AT+5", "AT+6", "AT+7"] {
+            assert_eq!(parser.add_remap(old, "AT+NEW"), Ok(()));
+        }
+        assert_eq!(parser.add_remap("AT+X", "AT+NEW"), Err(AtError::BufferFull));
+    }
+    assert_eq!((old.sets, new.sets), (0, 2));
+}
+
+#[test]
+fn read_only_commands_reject_set() {
+    /// Firmware version, queryable but never writable
+    struct Version;
+
+    impl AtContext for Version {
+        fn query(&mut self) -> AtResult<'static> {
+            Ok("1.2")
+        }
+
+        fn read_only(&self) -> bool {
+            true
+        }
+    }
+
+    let mut version = Version;
+    let commands: &mut [(&str, &mut Version)] = &mut [("AT+VER", &mut version)];
+    let mut parser = AtParser::new();
+    parser.set_commands(commands);
+
+    assert_eq!(parser.execute("AT+VER?"), Ok("1.2"));
+    assert_eq!(parser.execute("AT+VER=2.0"), Err(AtError::ReadOnly));
+    assert_eq!(parser.validate("AT+VER=2.0"), Err(AtError::ReadOnly));
+    assert_eq!(parser.validate("AT+VER?"), Ok(("AT+VER", FormKind::Query)));
+    assert_eq!(parser.execute("AT+VER"), Err(AtError::NotSupported));
+    assert_eq!(AtError::ReadOnly.code(), 10);
+}
+
+#[test]
+fn command_builder_assembles_a_function_pointer_table() {
+    #[derive(Default)]
+    struct Device {
+        led: bool,
+        resets: u32,
+    }
+
+    fn reset(device: &mut Device) -> AtResult<'static> {
+        device.resets += 1;
+        Ok("")
+    }
+
+    let table = AtCommandParser::<Device>::builder::<2>()
+        .on_query("AT+LED", |dev| Ok(if dev.led { "1" } else { "0" }))
+        .on_exec("AT+RST", reset)
+        .on_set("AT+LED", |dev, args| {
+            dev.led = args.get_u32_in(0, 0, 1)? == 1;
+            Ok("")
+        })
+        .help("AT+LED", "AT+LED=<0|1>")
+        .build()
+        .unwrap();
+    assert_eq!(table.commands().iter().map(|c| c.name).collect::<Vec<_>>(), ["AT+LED", "AT+RST"]);
+
+    let parser = table.parser();
+    let mut device = Device::default();
+    assert_eq!(parser.execute(&mut device, "AT+LED=1"), Ok(""));
+    assert_eq!(parser.execute(&mut device, "AT+LED?"), Ok("1"));
+    assert_eq!(parser.execute(&mut device, "AT+LED=?"), Ok("AT+LED=<0|1>"));
+    assert_eq!(parser.execute(&mut device, "AT+RST"), Ok(""));
+    assert_eq!(parser.execute(&mut device, "AT+RST?"), Err(AtError::NotSupported));
+    assert_eq!(device.resets, 1);
+
+    let overflow = AtCommandParser::<Device>::builder::<1>()
+        .on_exec("AT+RST", reset)
+        .on_exec("AT+X", reset)
+        .build();
+    assert!(matches!(overflow, Err(AtError::BufferFull)));
+}
+
+#[test]
+fn sentinel_table_stops_at_the_empty_name() {
+    use at_parser_rs::parser::SentinelEntry;
+    use core::mem::MaybeUninit;
+    use core::ptr;
+
+    static mut A: Probe = Probe { sets: 0 };
+    static mut B: Probe = Probe { sets: 0 };
+    // Shaped like `const struct entry table[] = { {"AT+A", &a}, ..., {NULL, NULL} };`
+    static TABLE: [SentinelEntry<Probe>; 3] = [
+        SentinelEntry { name: c"AT+A".as_ptr(), handler: &raw mut A },
+        SentinelEntry { name: c"AT+B".as_ptr(), handler: &raw mut B },
+        SentinelEntry { name: ptr::null(), handler: ptr::null_mut() },
+    ];
+    static EMPTY_END: [SentinelEntry<Probe>; 2] = [
+        SentinelEntry { name: c"AT+A".as_ptr(), handler: ptr::null_mut() },
+        SentinelEntry { name: c"".as_ptr(), handler: ptr::null_mut() },
+    ];
+
+    {
+        let mut storage = [const { MaybeUninit::uninit() }; 4];
+        // SAFETY: the table ends with a null name and `A`/`B` are only used
+        // through this parser
+        let mut parser = unsafe { AtParser::from_sentinel_table(TABLE.as_ptr(), &mut storage) }.unwrap();
+        assert_eq!(parser.len(), 2);
+        assert_eq!(parser.execute("AT+B=1"), Ok(""));
+        assert_eq!(parser.execute("=1"), Err(AtError::SyntaxAt(0)));
+    }
+    // SAFETY: the parser borrowing the modules is gone
+    assert_eq!(unsafe { (A.sets, B.sets) }, (0, 1));
+
+    let mut small = [const { MaybeUninit::uninit() }; 1];
+    // SAFETY: as above
+    let overflow = unsafe { AtParser::from_sentinel_table(TABLE.as_ptr(), &mut small) };
+    assert!(matches!(overflow, Err(ConfigError::TableFull)));
+
+    let mut storage = [const { MaybeUninit::uninit() }; 4];
+    // SAFETY: the table ends with an empty name
+    let missing = unsafe { AtParser::from_sentinel_table(EMPTY_END.as_ptr(), &mut storage) };
+    assert!(matches!(missing, Err(ConfigError::NoHandler("AT+A"))));
+}
+
+#[test]
+fn measured_length_matches_the_rendered_response() {
+    let mut flash = Flash;
+    assert_eq!(measure_response(&mut flash, AtForm::Exec), Ok("text\r\nOK\r\n".len()));
+    assert_eq!(measure_response(&mut flash, AtForm::Set(Args { raw: "raw" })), Ok(10));
+    assert_eq!(measure_response(&mut flash, AtForm::Set(Args { raw: "x" })), Err(AtError::InvalidArgs));
+    assert_eq!(measure_response(&mut flash, AtForm::Query), Err(AtError::NotSupported));
+
+    let mut probe = Probe::default();
+    assert_eq!(measure_response(&mut probe, AtForm::Query), Ok(7));
+    let commands: &mut [(&str, &mut Probe)] = &mut [("AT+A", &mut probe)];
+    let mut parser = AtParser::new();
+    parser.set_commands(commands);
+    let mut buf = [0u8; 16];
+    assert_eq!(parser.execute_into("AT+A?", &mut buf), Ok(7));
+}
+
+#[test]
+fn arg_spec_is_checked_before_set() {
+    fn set(count: &mut u32, _: Args) -> AtResult<'static> {
+        *count += 1;
+        Ok("")
+    }
+
+    let commands = [
+        AtCommand::named("AT+MOVE").set(set).arg_spec("n,n?"),
+        AtCommand::named("AT+NAME").set(set).arg_spec("s,b?"),
+        AtCommand::named("AT+RST").set(set).arg_spec(""),
+    ];
+    let parser = AtCommandParser::new(&commands);
+    assert_eq!(parser.self_check(), Ok(()));
+    let mut count = 0;
+
+    for input in ["AT+MOVE=5", "AT+MOVE=-5,+7", "AT+MOVE=5,", "AT+NAME=\"a b\"", "AT+NAME=\"a,b\"", "AT+NAME=\"a,b\",1", "AT+NAME=,1", "AT+RST="] {
+        assert_eq!(parser.execute(&mut count, input), Ok(""), "{}", input);
+    }
+    for input in ["AT+MOVE=", "AT+MOVE=x", "AT+MOVE=1,2,3", "AT+NAME=a,2", "AT+NAME=a,b,1", "AT+RST=1"] {
+        assert_eq!(parser.execute(&mut count, input), Err(AtError::InvalidArgs), "{}", input);
+    }
+    assert_eq!(count, 8);
+
+    let commands = [AtCommand::named("AT+X").set(set).arg_spec("n?,n")];
+    assert_eq!(AtCommandParser::new(&commands).self_check(), Err(ConfigError::InvalidArgSpec("AT+X")));
+    assert_eq!(AtCommandParser::new(&commands).execute(&mut count, "AT+X=1,2"), Err(AtError::InvalidArgs));
+}
+
+#[test]
+fn configured_line_terminators_are_stripped() {
+    let mut a = Probe::default();
+    {
+        let commands: &mut [(&str, &mut Probe)] = &mut [("AT+A", &mut a)];
+        let mut parser = AtParser::new();
+        parser.set_commands(commands);
+        assert_eq!(parser.execute("AT+A?;"), Err(AtError::UnknownCommand));
+
+        parser.set_line_terminators(&[';', '\x04']);
+        assert_eq!(parser.execute("AT+A?;"), Ok("1"));
+        assert_eq!(parser.execute("AT+A=1;\x04 ;\r\n"), Ok(""));
+        let mut out = String::new();
+        parser.execute_line("AT+A=1;AT+A?;", &mut out).unwrap();
+        assert_eq!(out, "1\r\nOK\r\n");
+        assert_eq!(parser.settings().line_terminators, [';', '\x04']);
+    }
+    assert_eq!(a.sets, 2);
+}
+
+#[test]
+fn fallback_handles_unmatched_commands() {
+    use at_parser_rs::context::AtFallback;
+
+    /// Fallback recording what it was handed
+    #[derive(Default)]
+    struct Peer {
+        seen: Vec<(String, String, FormKind)>,
+    }
+
+    impl AtFallback for Peer {
+        fn handle(&mut self, input: &str, name: &str, form: FormKind) -> AtResult<'static> {
+            self.seen.push((input.to_owned(), name.to_owned(), form));
+            Ok("PEER")
+        }
+    }
+
+    let mut a = Probe::default();
+    let mut peer = Peer::default();
+    {
+        let commands: &mut [(&str, &mut Probe)] = &mut [("AT+A", &mut a)];
+        let mut parser = AtParser::new();
+        parser.set_commands(commands);
+        parser.set_fallback(Some(&mut peer));
+
+        assert_eq!(parser.execute("AT+A=1"), Ok(""));
+        assert_eq!(parser.execute(" AT+PEER=1,2 "), Ok("PEER"));
+        assert_eq!(parser.execute("AT+PEER?"), Ok("PEER"));
+        assert_eq!(parser.execute("AT+PEER=?"), Ok("PEER"));
+        assert_eq!(parser.execute("AT+PEER"), Ok("PEER"));
+        assert_eq!(parser.validate("AT+PEER"), Err(AtError::UnknownCommand));
+
+        parser.set_fallback(None);
+        assert_eq!(parser.execute("AT+PEER"), Err(AtError::UnknownCommand));
+    }
+    let kinds: Vec<FormKind> = peer.seen.iter().map(|(_, _, kind)| *kind).collect();
+    assert_eq!(kinds, [FormKind::Set, FormKind::Query, FormKind::Test, FormKind::Exec]);
+    assert_eq!(peer.seen[0].0, "AT+PEER=1,2");
+    assert!(peer.seen.iter().all(|(_, name, _)| name == "AT+PEER"));
+    assert_eq!(a.sets, 1);
+}
+
+#[test]
+fn parser_can_be_shared_between_threads() {
+    use std::sync::Mutex;
+
+    fn assert_send<S: std::marker::Send>() {}
+    assert_send::<AtParser<'static, Probe>>();
+
+    let mut a = Probe::default();
+    {
+        let commands: &mut [(&str, &mut Probe)] = &mut [("AT+A", &mut a)];
+        let mut parser = AtParser::new();
+        parser.set_commands(commands);
+        // Stand-in for the `osal_rs` mutex guarding `sync::SyncAtParser`
+        let shared = Mutex::new(parser);
+
+        std::thread::scope(|s| {
+            for _ in 0..2 {
+                s.spawn(|| {
+                    for _ in 0..50 {
+                        assert_eq!(shared.lock().unwrap().execute("AT+A=1"), Ok(""));
+                    }
+                });
+            }
+        });
+        assert_eq!(shared.lock().unwrap().execute("AT+A?"), Ok("1"));
+    }
+    assert_eq!(a.sets, 100);
+}
+
+/// LED taking `<state>,<brightness>`
+#[derive(Default)]
+struct Led {
+    sets: u32,
+}
+
+impl AtContext for Led {
+    fn set(&mut self, _args: Args) -> AtResult<'static> {
+        self.sets += 1;
+        Ok("")
+    }
+
+    fn arity(&self) -> Option<usize> {
+        Some(2)
+    }
+}
+
+#[test]
+fn extra_args_are_rejected_only_in_strict_mode() {
+    let (mut led, mut a) = (Led::default(), Probe::default());
+    {
+        let commands: &mut [(&str, &mut Led)] = &mut [("AT+LED", &mut led)];
+        let mut parser = AtParser::new();
+        parser.set_commands(commands);
+
+        assert_eq!(parser.execute("AT+LED=1,50,99"), Ok(""));
+        assert_eq!(parser.validate("AT+LED=1,50,99"), Ok(("AT+LED", FormKind::Set)));
+        parser.set_strict_args(true);
+        assert_eq!(parser.execute("AT+LED=1,50,99"), Err(AtError::InvalidArgs));
+        assert_eq!(parser.validate("AT+LED=1,50,99"), Err(AtError::InvalidArgs));
+        assert_eq!(parser.execute("AT+LED=1,50"), Ok(""));
+        assert_eq!(parser.execute("AT+LED=1"), Ok(""));
+    }
+    {
+        // Modules declaring no arity are never checked
+        let commands: &mut [(&str, &mut Probe)] = &mut [("AT+A", &mut a)];
+        let mut parser = AtParser::new();
+        parser.set_commands(commands);
+        parser.set_strict_args(true);
+        assert_eq!(parser.execute("AT+A=1,2,3,4"), Ok(""));
+    }
+    assert_eq!((led.sets, a.sets), (3, 1));
+}
+
+#[test]
+fn max_args_rejects_the_first_argument_over_the_limit() {
+    use at_parser_rs::parser::DEFAULT_MAX_ARGS;
+
+    let mut a = Probe::default();
+    {
+        let commands: &mut [(&str, &mut Probe)] = &mut [("AT+A", &mut a)];
+        let mut parser = AtParser::new();
+        parser.set_commands(commands);
+
+        let at_limit = format!("AT+A={}", vec!["1"; DEFAULT_MAX_ARGS].join(","));
+        assert_eq!(parser.execute(&at_limit), Ok(""));
+        assert_eq!(parser.execute(&format!("{},1", at_limit)), Err(AtError::InvalidArgs));
+
+        parser.set_max_args(3);
+        assert_eq!(parser.settings().max_args, 3);
+        assert_eq!(parser.execute("AT+A=1,2,3"), Ok(""));
+        assert_eq!(parser.execute("AT+A=1,2,3,4"), Err(AtError::InvalidArgs));
+        // Empty fields count too
+        assert_eq!(parser.execute("AT+A=1,,,"), Err(AtError::InvalidArgs));
+        assert_eq!(parser.validate("AT+A=1,2,3,4"), Err(AtError::InvalidArgs));
+
+        // No cap at all
+        parser.set_max_args(usize::MAX);
+        assert_eq!(parser.execute(&format!("{},1", at_limit)), Ok(""));
+        parser.set_max_args(0);
+        assert_eq!(parser.execute("AT+A=1"), Err(AtError::InvalidArgs));
+    }
+    // Rejected lines never reach the handler
+    assert_eq!(a.sets, 3);
+}
+
+/// Shared peripheral handed to every handler by `execute_with`
+struct Uart {
+    baud: u32,
+    writes: u32,
+}
+
+/// Module configuring the shared UART
+struct BaudRate;
+
+impl AtContext for BaudRate {}
+
+impl at_parser_rs::context::AtContextWith<Uart> for BaudRate {
+    fn query_ctx(&mut self, uart: &mut Uart) -> AtResult<'static> {
+        Ok(if uart.baud == 115200 { "115200" } else { "9600" })
+    }
+
+    fn set_ctx(&mut self, args: Args, uart: &mut Uart) -> AtResult<'static> {
+        uart.baud = args.get_u32(0)?;
+        uart.writes += 1;
+        Ok("")
+    }
+}
+
+#[test]
+fn execute_with_passes_the_shared_context() {
+    let (mut a, mut b) = (BaudRate, BaudRate);
+    let commands: &mut [(&str, &mut BaudRate)] = &mut [("AT+IPR", &mut a), ("AT+IPR2", &mut b)];
+    let mut parser = AtParser::new();
+    parser.set_commands(commands);
+    let mut uart = Uart { baud: 9600, writes: 0 };
+
+    assert_eq!(parser.execute_with("AT+IPR?", &mut uart), Ok("9600"));
+    assert_eq!(parser.execute_with("AT+IPR=115200", &mut uart), Ok(""));
+    // Both modules see the same context
+    assert_eq!(parser.execute_with("AT+IPR2?", &mut uart), Ok("115200"));
+    assert_eq!(parser.execute_with("AT+IPR2=x", &mut uart), Err(AtError::InvalidArgs));
+    assert_eq!((uart.baud, uart.writes), (115200, 1));
+
+    // Forms without a context-aware handler fall back to `AtContext`
+    assert_eq!(parser.execute_with("AT+IPR", &mut uart), Err(AtError::NotSupported));
+    assert_eq!(parser.execute_with("AT+NOPE", &mut uart), Err(AtError::UnknownCommand));
+    // The plain path never sees the context
+    assert_eq!(parser.execute("AT+IPR?"), Err(AtError::NotSupported));
+}
+
+#[test]
+fn complete_lists_every_name_sharing_the_prefix() {
+    let (mut a, mut b, mut c, mut d) = (Probe::default(), Probe::default(), Probe::default(), Probe::default());
+    let commands: &mut [(&str, &mut Probe)] = &mut [
+        ("AT+WIFI", &mut a),
+        ("AT+WIFISCAN", &mut b),
+        ("AT+WPS", &mut c),
+    ];
+    let group: &mut [(&str, &mut Probe)] = &mut [("AT+BLE", &mut d)];
+    let mut parser = AtParser::new();
+    parser.set_commands(commands);
+    parser.add_group(group).unwrap();
+
+    let complete = |partial| parser.complete(partial).collect::<Vec<_>>();
+    assert_eq!(complete("AT+WIFIS"), ["AT+WIFISCAN"]);
+    // Ambiguous prefixes yield every candidate, in lookup order
+    assert_eq!(complete("AT+WI"), ["AT+WIFI", "AT+WIFISCAN"]);
+    assert_eq!(complete("AT+W"), ["AT+WIFI", "AT+WIFISCAN", "AT+WPS"]);
+    // A complete name is its own candidate
+    assert_eq!(complete("AT+WIFI"), ["AT+WIFI", "AT+WIFISCAN"]);
+    // An empty prefix matches everything, groups included
+    assert_eq!(complete(""), ["AT+WIFI", "AT+WIFISCAN", "AT+WPS", "AT+BLE"]);
+    assert!(complete("AT+wi").is_empty());
+    assert!(complete("AT+X").is_empty());
+}
+
+/// Module only reporting success or failure of its Set
+#[derive(Default)]
+struct Volume {
+    level: u32,
+}
+
+impl AtContext for Volume {
+    fn set_void(&mut self, args: Args) -> Result<(), AtError> {
+        self.level = args.get_u32(0)?;
+        Ok(())
+    }
+}
+
+#[test]
+fn set_void_renders_a_bare_ok() {
+    let mut volume = Volume::default();
+    {
+        let commands: &mut [(&str, &mut Volume)] = &mut [("AT+VOL", &mut volume)];
+        let mut parser = AtParser::new();
+        parser.set_commands(commands);
+        parser.set_response_prefix(true);
+
+        let mut out = String::new();
+        parser.execute_framed("AT+VOL=7", &mut out).unwrap();
+        assert_eq!(out, "OK\r\n");
+
+        out.clear();
+        parser.execute_framed("AT+VOL=x", &mut out).unwrap();
+        assert_eq!(out, "ERROR\r\n");
+        assert_eq!(parser.execute("AT+VOL=3"), Ok(""));
+    }
+    assert_eq!(volume.level, 3);
+}
+
+/// Module that would open a data phase after any form
+#[derive(Default)]
+struct Modem {
+    asked: u32,
+}
+
+impl AtContext for Modem {
+    fn exec(&self) -> AtResult<'static> {
+        Ok("")
+    }
+
+    fn query(&mut self) -> AtResult<'static> {
+        Ok("1")
+    }
+
+    fn test(&mut self) -> AtResult<'static> {
+        Ok("(0,1)")
+    }
+
+    fn data_phase(&mut self) -> Option<&'static str> {
+        self.asked += 1;
+        Some("CONNECT")
+    }
+}
+
+#[test]
+fn only_exec_and_set_can_open_a_data_phase() {
+    let mut modem = Modem::default();
+    {
+        let commands: &mut [(&str, &mut Modem)] = &mut [("ATD", &mut modem)];
+        let mut parser = AtParser::new();
+        parser.set_commands(commands);
+
+        let mut out = String::new();
+        parser.execute_framed("ATD?", &mut out).unwrap();
+        parser.execute_framed("ATD=?", &mut out).unwrap();
+        assert_eq!(out, "1\r\nOK\r\n(0,1)\r\nOK\r\n");
+        assert!(!parser.data_mode());
+
+        out.clear();
+        parser.execute_framed("ATD", &mut out).unwrap();
+        assert_eq!(out, "CONNECT\r\n");
+        assert!(parser.data_mode());
+    }
+    assert_eq!(modem.asked, 1);
+}
+
+#[test]
+fn validate_reports_what_execute_would_without_running_handlers() {
+    let mut a = Probe::default();
+    {
+        let commands: &mut [(&str, &mut Probe)] = &mut [("AT+A", &mut a)];
+        let mut parser = AtParser::new();
+        parser.set_commands(commands);
+
+        assert_eq!(parser.validate("  AT+A=1 "), Ok(("AT+A", FormKind::Set)));
+        assert_eq!(parser.validate("AT+A?"), Ok(("AT+A", FormKind::Query)));
+        assert_eq!(parser.validate("AT+A"), Ok(("AT+A", FormKind::Exec)));
+        assert_eq!(parser.validate("AT+B=1"), Err(AtError::UnknownCommand));
+        assert_eq!(parser.validate("AT+A==1"), Err(AtError::SyntaxAt(5)));
+        assert_eq!(parser.execute("AT+A==1"), Err(AtError::SyntaxAt(5)));
+
+        parser.set_busy(true);
+        assert_eq!(parser.validate("AT+A=1"), Err(AtError::Busy));
+        parser.set_busy(false);
+
+        // Execution-only behaviors: empty lines and the bare ping are
+        // answered by `execute` but match no command
+        assert_eq!(parser.execute(""), Ok(""));
+        assert_eq!(parser.validate(""), Err(AtError::UnknownCommand));
+        assert_eq!(parser.execute("AT"), Ok(""));
+        assert_eq!(parser.validate("AT"), Err(AtError::UnknownCommand));
+    }
+    // Nothing reached the handler
+    assert_eq!(a.sets, 0);
+}
+
+#[test]
+fn command_suffix_strips_every_at_prefix() {
+    use at_parser_rs::command_suffix;
+
+    assert_eq!(command_suffix("AT+LED"), "LED");
+    assert_eq!(command_suffix("AT&V"), "V");
+    assert_eq!(command_suffix("ATE"), "E");
+    assert_eq!(command_suffix("AT"), "");
+    assert_eq!(command_suffix("AT+"), "");
+    assert_eq!(command_suffix("RING"), "RING");
+    assert_eq!(command_suffix("at+led"), "at+led");
+    // The bare `AT` rule also applies to unprefixed names starting with AT
+    assert_eq!(command_suffix("ATTACH"), "TACH");
+    assert_eq!(command_suffix("AT+ATTACH"), "ATTACH");
+}
+
+#[test]
+fn framed_responses_follow_the_payload_kind() {
+    use at_parser_rs::{Response, SUPPRESSED};
+
+    assert_eq!(Response::from(""), Response::None);
+    assert_eq!(Response::from("1"), Response::Text("1"));
+    assert_eq!(Response::from(SUPPRESSED), Response::Suppressed);
+
+    let mut a = Probe::default();
+    let commands: &mut [(&str, &mut Probe)] = &mut [("AT+A", &mut a)];
+    let mut parser = AtParser::new();
+    parser.set_commands(commands);
+
+    let mut out = String::new();
+    parser.execute_framed("AT+A", &mut out).unwrap();
+    parser.execute_framed("AT+A?", &mut out).unwrap();
+    parser.execute_framed("AT+X", &mut out).unwrap();
+    assert_eq!(out, "OK\r\n1\r\nOK\r\nERROR\r\n");
+}
+
+#[test]
+fn groups_are_searched_after_the_main_table() {
+    use at_parser_rs::parser::MAX_GROUPS;
+
+    let mut a = Probe::default();
+    let mut shadow = Probe::default();
+    let mut b = Probe::default();
+    {
+        let commands: &mut [(&str, &mut Probe)] = &mut [("AT+A", &mut a)];
+        let group: &mut [(&str, &mut Probe)] = &mut [("AT+A", &mut shadow), ("AT+B", &mut b)];
+        let mut parser = AtParser::new();
+        parser.set_commands(commands);
+        parser.add_group(group).unwrap();
+        for _ in 1..MAX_GROUPS {
+            parser.add_group(&mut []).unwrap();
+        }
+        assert_eq!(parser.add_group(&mut []), Err(AtError::BufferFull));
+
+        assert_eq!(parser.execute("AT+A=1"), Ok(""));
+        assert_eq!(parser.execute("AT+B=1"), Ok(""));
+        assert_eq!(parser.execute("AT+C=1"), Err(AtError::UnknownCommand));
+    }
+    assert_eq!(a.sets, 1);
+    assert_eq!(shadow.sets, 0);
+    assert_eq!(b.sets, 1);
+}
+
+/// Module answering its query with one row per scanned network
+struct Networks;
+
+impl AtContext for Networks {
+    fn exec(&self) -> AtResult<'static> {
+        Ok("")
+    }
+
+    fn query_iter(&mut self) -> impl Iterator<Item = AtResult<'static>> {
+        ["\"home\",-40", "\"guest\",-72"].into_iter().map(Ok)
+    }
+}
+
+#[test]
+fn query_iter_writes_one_line_per_row() {
+    let mut scan = Networks;
+    let commands: &mut [(&str, &mut Networks)] = &mut [("AT+SCAN", &mut scan)];
+    let mut parser = AtParser::new();
+    parser.set_commands(commands);
+
+    let mut out = String::new();
+    parser.execute_framed("AT+SCAN?", &mut out).unwrap();
+    assert_eq!(out, "\"home\",-40\r\n\"guest\",-72\r\nOK\r\n");
+}
+
+#[test]
+fn response_filter_applies_to_successes_only() {
+    fn spell(payload: &'static str) -> &'static str {
+        match payload {
+            "1" => "one",
+            other => other,
+        }
+    }
+
+    let mut a = Probe::default();
+    let commands: &mut [(&str, &mut Probe)] = &mut [("AT+A", &mut a)];
+    let mut parser = AtParser::new();
+    parser.set_commands(commands);
+    parser.set_response_filter(Some(spell));
+
+    assert_eq!(parser.execute("AT+A?"), Ok("one"));
+    let mut out = String::new();
+    parser.execute_framed("AT+A?", &mut out).unwrap();
+    parser.execute_framed("AT+X", &mut out).unwrap();
+    assert_eq!(out, "one\r\nOK\r\nERROR\r\n");
+
+    parser.set_response_filter(None);
+    assert_eq!(parser.execute("AT+A?"), Ok("1"));
+}
@@ -0,0 +1,59 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+
+
+//! Integration tests for the S-register bank
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use at_parser_rs::registers::SRegisters;
+use at_parser_rs::AtError;
+
+/// Number of `on_change` calls and the last value reported
+static CHANGES: AtomicU32 = AtomicU32::new(0);
+static LAST: AtomicU32 = AtomicU32::new(0);
+
+fn persist(n: u8, value: u32) {
+    CHANGES.fetch_add(1, Ordering::Relaxed);
+    LAST.store(u32::from(n) << 16 | value, Ordering::Relaxed);
+}
+
+#[test]
+fn on_change_fires_only_for_actual_changes() {
+    let mut regs = SRegisters::new([0, 13, 10]);
+    regs.on_change(Some(persist));
+
+    regs.set(1, 13).unwrap();
+    assert_eq!(CHANGES.load(Ordering::Relaxed), 0);
+
+    regs.set(1, 3).unwrap();
+    assert_eq!(CHANGES.load(Ordering::Relaxed), 1);
+    assert_eq!(LAST.load(Ordering::Relaxed), 1 << 16 | 3);
+    regs.set(1, 3).unwrap();
+    assert_eq!(CHANGES.load(Ordering::Relaxed), 1);
+
+    assert_eq!(regs.set(3, 1), Err(AtError::InvalidArgs));
+    assert_eq!(CHANGES.load(Ordering::Relaxed), 1);
+
+    regs.on_change(None);
+    regs.set(2, 0).unwrap();
+    assert_eq!(CHANGES.load(Ordering::Relaxed), 1);
+    assert_eq!(regs.get(2), Some(0));
+}
@@ -0,0 +1,63 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+
+//! Corpus of malformed AT lines
+//! Integration tests for link-time command registration
+
+use at_parser_rs::at_register;
+use at_parser_rs::command::{AtCommand, AtCommandParser};
+use at_parser_rs::registry::collect_registered;
+use at_parser_rs::AtResult;
+
+#[derive(Default)]
+struct Device {
+    led: bool,
+}
+
+fn led_query(device: &mut Device) -> AtResult<'static> {
+    Ok(if device.led { "1" } else { "0" })
+}
+
+fn led_exec(device: &mut Device) -> AtResult<'static> {
+    device.led = !device.led;
+    Ok("")
+}
+
+fn version_exec(_: &mut Device) -> AtResult<'static> {
+    Ok("1.0")
+}
+
+at_register!(LED: Device = AtCommand::named("AT+LED").exec(led_exec).query(led_query));
+at_register!(VERSION: Device = AtCommand::named("AT+VER").exec(version_exec));
+
+#[test]
+fn registered_commands_are_collected_from_the_section() {
+    // SAFETY: every command of this binary uses the `Device` context
+    let commands = unsafe { collect_registered::<Device>() };
+    let mut names: Vec<&str> = commands.iter().map(|c| c.name).collect();
+    names.sort();
+    assert_eq!(names, ["AT+LED", "AT+VER"]);
+
+    let parser = AtCommandParser::new(commands);
+    let mut device = Device::default();
+    assert_eq!(parser.execute(&mut device, "AT+LED"), Ok(""));
+    assert_eq!(parser.execute(&mut device, "AT+LED?"), Ok("1"));
+    assert_eq!(parser.execute(&mut device, "AT+VER"), Ok("1.0"));
+}
@@ -0,0 +1,180 @@
+/***************************************************************************
+ *
+ * AT Command Parser
+ * Copyright (C) 2026 Antonio Salsi <passy.linux@zresa.it>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ ***************************************************************************/
+
+
+//! Integration tests for the response helpers
+
+use at_parser_rs::response::chunks;
+
+#[test]
+fn chunks_prefer_field_separators() {
+    let parts: Vec<&str> = chunks("+SCAN: aa,bbb,c\r\nOK\r\n", 10).collect();
+    assert_eq!(parts, ["+SCAN: aa,", "bbb,c\r\n", "OK\r\n"]);
+}
+
+#[test]
+fn chunks_split_long_fields_on_char_boundaries() {
+    let parts: Vec<&str> = chunks("abcdefgh", 3).collect();
+    assert_eq!(parts, ["abc", "def", "gh"]);
+
+    let parts: Vec<&str> = chunks("aéé", 2).collect();
+    assert_eq!(parts, ["a", "é", "é"]);
+
+    let parts: Vec<&str> = chunks("é", 1).collect();
+    assert_eq!(parts, ["é"]);
+}
+
+#[test]
+fn writer_formats_values_with_units() {
+    use at_parser_rs::response::ResponseWriter;
+
+    let mut buf = [0u8; 64];
+    let mut w = ResponseWriter::new(&mut buf);
+    w.fixed_with_unit(235, 1, "C").unwrap();
+    w.value_with_unit_i32(1013, "hPa").unwrap();
+    w.fixed_with_unit(-5, 2, "V").unwrap();
+    w.fixed_with_unit(7, 0, "%").unwrap();
+    assert_eq!(w.as_str(), "23.5C1013hPa-0.05V7%");
+}
+
+#[test]
+fn bool_style_is_a_parser_setting() {
+    use at_parser_rs::parser::AtParser;
+    use at_parser_rs::response::{BoolStyle, ResponseWriter};
+
+    let mut parser: AtParser<()> = AtParser::new();
+    assert_eq!(parser.bool_style(), BoolStyle::ZeroOne);
+    assert_eq!(parser.bool_style().text(true), "1");
+    assert_eq!(parser.bool_style().text(false), "0");
+
+    let saved = parser.settings();
+    parser.set_bool_style(BoolStyle::OnOff);
+    let mut buf = [0u8; 8];
+    let mut writer = ResponseWriter::new(&mut buf);
+    writer.write_bool(false, parser.bool_style()).unwrap();
+    assert_eq!(writer.as_str(), "OFF");
+
+    // Independent parsers keep their own style
+    let other: AtParser<()> = AtParser::new();
+    assert_eq!(other.bool_style(), BoolStyle::ZeroOne);
+
+    assert_eq!(BoolStyle::TrueFalse.text(true), "true");
+    parser.apply_settings(saved);
+    assert_eq!(parser.bool_style(), BoolStyle::ZeroOne);
+}
+
+#[test]
+fn urcs_carry_an_optional_timestamp() {
+    use at_parser_rs::urc::UrcWriter;
+
+    fn fake_clock() -> u64 {
+        1500
+    }
+
+    let mut urc = UrcWriter::new();
+    let mut out = String::new();
+    urc.emit(&mut out, "RING", "").unwrap();
+    urc.emit(&mut out, "AT+WIFI", "DISCONNECTED").unwrap();
+
+    urc.set_clock(Some(fake_clock));
+    urc.emit(&mut out, "RING", "").unwrap();
+    urc.emit(&mut out, "WIFI", "DISCONNECTED").unwrap();
+
+    assert_eq!(out, "+RING\r\n+WIFI: DISCONNECTED\r\n+RING: 1500\r\n+WIFI: 1500,DISCONNECTED\r\n");
+    // Names starting with AT keep their prefix (see `command_suffix`)
+    out.clear();
+    urc.set_clock(None);
+    urc.emit(&mut out, "AT+ATTACH", "1").unwrap();
+    assert_eq!(out, "+ATTACH: 1\r\n");
+}
+
+#[test]
+fn templates_fill_named_placeholders() {
+    use at_parser_rs::response::write_template;
+
+    let mut out = String::new();
+    write_template(&mut out, "brightness: {min}-{max}", &[("min", &0), ("max", &255)]).unwrap();
+    assert_eq!(out, "brightness: 0-255");
+
+    let mut out = String::new();
+    write_template(&mut out, "{{max}} {unknown} {max", &[("max", &7)]).unwrap();
+    assert_eq!(out, "{max} {unknown} {max");
+}
+
+#[test]
+fn counted_list_writes_the_count_before_the_fields() {
+    use at_parser_rs::response::ResponseWriter;
+
+    let mut buf = [0u8; 32];
+    let mut w = ResponseWriter::new(&mut buf);
+    let mut list = w.counted_list(3).unwrap();
+    for field in ["a", "b", "c"] {
+        list.field(field).unwrap();
+    }
+    list.finish().unwrap();
+    w.counted_list(0).unwrap().finish().unwrap();
+    assert_eq!(w.as_str(), "3,a,b,c0");
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "declared 2 fields but 1 were written")]
+fn counted_list_checks_the_count_in_debug_builds() {
+    use at_parser_rs::response::ResponseWriter;
+
+    let mut buf = [0u8; 32];
+    let mut w = ResponseWriter::new(&mut buf);
+    let mut list = w.counted_list(2).unwrap();
+    list.field(1).unwrap();
+    let _ = list.finish();
+}
+
+#[test]
+fn padded_fields_are_aligned_and_truncated() {
+    use at_parser_rs::response::{Align, ResponseWriter};
+
+    let mut buf = [0u8; 64];
+    let mut w = ResponseWriter::new(&mut buf);
+    w.field_padded("E", 4, '.', Align::Left).unwrap();
+    w.field_padded(57600, 8, ' ', Align::Right).unwrap();
+    w.field_padded("é", 2, '0', Align::Right).unwrap();
+    w.field_padded("TOOLONG", 4, ' ', Align::Left).unwrap();
+    w.field_padded(-12345, 3, ' ', Align::Right).unwrap();
+    w.field_padded("x", 0, ' ', Align::Left).unwrap();
+    assert_eq!(w.as_str(), "E...   576000éTOOL-12");
+}
+
+#[test]
+fn typed_fields_quote_text_only() {
+    use at_parser_rs::response::ResponseWriter;
+    use core::fmt::Write;
+
+    let mut buf = [0u8; 64];
+    let mut w = ResponseWriter::new(&mut buf);
+    w.field_str("home").unwrap();
+    w.write_str(",").unwrap();
+    w.field_u32(42).unwrap();
+    w.write_str(",").unwrap();
+    w.field_i32(-7).unwrap();
+    w.write_str(",").unwrap();
+    w.field_str("a\"b\\c").unwrap();
+    w.write_str(",").unwrap();
+    w.field_str("").unwrap();
+    assert_eq!(w.as_str(), "\"home\",42,-7,\"a\\22b\\5Cc\",\"\"");
+}